@@ -1,61 +1,89 @@
 use chrono::{DateTime, Utc};
 
-use matrix_sdk::config::{RequestConfig, SyncSettings};
+use matrix_sdk::attachment::AttachmentConfig;
+use matrix_sdk::authentication::matrix::MatrixSession;
+use matrix_sdk::config::{RequestConfig, SyncSettings, SyncToken};
+use matrix_sdk::encryption::{BackupDownloadStrategy, EncryptionSettings};
 use matrix_sdk::event_handler::Ctx;
-use matrix_sdk::room::RoomMember;
+use matrix_sdk::media::{MediaFormat, MediaRequestParameters};
+use matrix_sdk::room::{MessagesOptions, RoomMember};
 use matrix_sdk::ruma::events::reaction::{OriginalSyncReactionEvent, ReactionEventContent};
-use matrix_sdk::ruma::events::relation::Annotation;
+use matrix_sdk::ruma::events::relation::{Annotation, RelationType, Thread};
+use matrix_sdk::ruma::events::room::member::MembershipState;
 use matrix_sdk::ruma::events::room::message::{
-    FileMessageEventContent, MessageType, OriginalSyncRoomMessageEvent, RoomMessageEventContent,
+    OriginalSyncRoomMessageEvent, Relation, RoomMessageEventContent,
 };
 use matrix_sdk::ruma::events::room::redaction::SyncRoomRedactionEvent;
-use matrix_sdk::ruma::events::room::MediaSource;
-use matrix_sdk::ruma::events::Mentions;
-use matrix_sdk::ruma::{EventId, OwnedMxcUri, RoomId, ServerName, UserId};
-use matrix_sdk::{Client, Room, RoomState};
+use matrix_sdk::ruma::events::{
+    AnySyncMessageLikeEvent, AnySyncTimelineEvent, Mentions, SyncMessageLikeEvent,
+};
+use matrix_sdk::ruma::{
+    EventId, OwnedDeviceId, OwnedEventId, OwnedUserId, RoomId, ServerName, UserId,
+};
+use matrix_sdk::{Client, LoopCtrl, Room, RoomState, SessionMeta, SessionTokens};
 
 use regex::Regex;
 
+use std::collections::HashMap;
 use std::env;
 use std::fmt::Write;
+use std::fs;
 use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
 use crate::utils::MessageEventExt;
-use crate::{render, utils, BotMessageType as BotMsgType, Config, News, NewsStore, ReactionType};
+use crate::{
+    markdown, render, utils, BotMessageType as BotMsgType, Bundle, BundleFormat, Config, Edition,
+    EditorEdit, Error, FollowUp, MediaFile, Mention, News, NewsStore, Output, ReactionPermission,
+    ReactionType, RelatedNews, RenderedOutput, Session, SourceMembership, Templates,
+};
 
 #[derive(Clone)]
 pub struct Bot {
     config: Config,
     news_store: Arc<Mutex<NewsStore>>,
+    templates: Arc<Mutex<Templates>>,
     client: Client,
     reporting_room: Room,
     admin_room: Room,
+    /// Set while replaying missed events, the reporters already got notified about these
+    catching_up: Arc<AtomicBool>,
 }
 
 impl Bot {
     pub async fn run() {
         let config_result = Config::read();
         let config = config_result.config;
-        let news_store = Arc::new(Mutex::new(NewsStore::read()));
+        let news_store = match NewsStore::read() {
+            Ok(news_store) => news_store,
+            Err(err) => {
+                error!("Unable to open news store: {}", err);
+                std::process::exit(1);
+            }
+        };
+        let news_store = Arc::new(Mutex::new(news_store));
+        let template_dir = env::var("TEMPLATE_DIR").ok().map(PathBuf::from);
+        let template_names = config.outputs().into_iter().map(|o| o.template);
+        let templates = Templates::new(template_dir, template_names.collect());
+        let templates = Arc::new(Mutex::new(templates));
 
         let username = config.bot_user_id.as_str();
-        let password = env::var("BOT_PASSWORD").expect("BOT_PASSWORD env variable not specified");
-
         let user = UserId::parse(username).expect("Unable to parse bot user id");
-        let server_name = ServerName::parse(user.server_name()).unwrap();
-        let request_config = RequestConfig::new().force_auth();
 
-        let mut client_builder = Client::builder()
-            .server_name(&server_name)
-            .request_config(request_config);
-        if let Ok(value) = env::var("HOMESERVER_URL") {
-            client_builder = client_builder.homeserver_url(value);
-        }
-        let client = client_builder.build().await.unwrap();
+        // The session, room state and encryption keys are kept in the state directory
+        let state_dir = PathBuf::from(env::var("STATE_DIR").unwrap_or("./state".into()));
+        fs::create_dir_all(&state_dir).expect("Unable to create state directory");
+        let (client, session) = Self::connect(&user, &state_dir).await;
 
-        Self::login(&client, user.localpart(), &password).await;
+        info!("Doing the initial sync…");
+        let sync_token = SyncToken::from_optional_token(session.sync_token.clone());
+        client
+            .sync_once(SyncSettings::new().token(sync_token))
+            .await
+            .expect("Unable to sync");
 
         // Get matrix rooms IDs
         let reporting_room_id = RoomId::parse(config.reporting_room_id.as_str()).unwrap();
@@ -71,16 +99,21 @@ impl Bot {
                 room.join()
                     .await
                     .expect("Hebbot could not join the admin room");
+            } else if config.editor_sources.space_id.as_deref() == Some(room.room_id()) {
+                room.join()
+                    .await
+                    .expect("Hebbot could not join the editors space");
             } else {
                 info!("Ignored room: {}", room.room_id());
             }
         }
 
         // Sync to make sure that the bot is aware of the newly joined rooms
-        client
+        let initial_sync_token = client
             .sync_once(SyncSettings::new())
             .await
-            .expect("Unable to sync");
+            .expect("Unable to sync")
+            .next_batch;
 
         let reporting_room = client
             .get_room(&reporting_room_id)
@@ -93,14 +126,43 @@ impl Bot {
         let bot = Self {
             config,
             news_store,
+            templates,
             client,
             reporting_room,
             admin_room,
+            catching_up: Arc::default(),
         };
 
         bot.send_message("✅ Started hebbot!", BotMsgType::AdminRoomPlainNotice)
             .await;
 
+        // Fetch the cross-signing keys and enable the key backup
+        if let Ok(recovery_key) = env::var("RECOVERY_KEY") {
+            if let Err(err) = bot
+                .client
+                .encryption()
+                .recovery()
+                .recover(&recovery_key)
+                .await
+            {
+                let msg = format!("❌ Unable to recover encryption keys: {}", err);
+                bot.send_message(&msg, BotMsgType::AdminRoomPlainNotice)
+                    .await;
+            }
+        }
+
+        // Editors can't be resolved from a space the bot isn't part of
+        if let Some(space_id) = &bot.config.editor_sources.space_id {
+            if bot.client.get_room(space_id).is_none() {
+                let msg = format!(
+                    "⚠️ The bot isn’t a member of the editors space {}, invite it to resolve the editors.",
+                    space_id
+                );
+                bot.send_message(&msg, BotMsgType::AdminRoomPlainNotice)
+                    .await;
+            }
+        }
+
         // Send warnings
         let warnings = utils::format_messages(true, &config_result.warnings);
         if !config_result.warnings.is_empty() {
@@ -115,6 +177,19 @@ impl Bot {
                 .await;
         }
 
+        // Validate templates
+        let template_result = bot.templates.lock().unwrap().load();
+        if let Err(error) = template_result {
+            let msg = format!("❌ Could not load templates: <pre>{}</pre>", error);
+            bot.send_message(&msg, BotMsgType::AdminRoomHtmlNotice)
+                .await;
+        }
+
+        // Process the events which were sent while the bot was offline
+        if let Some(last_sync_token) = &session.sync_token {
+            bot.catch_up(last_sync_token, &initial_sync_token).await;
+        }
+
         // Setup event handlers
         bot.client.add_event_handler_context(bot.clone());
 
@@ -123,29 +198,229 @@ impl Bot {
         bot.client.add_event_handler(Self::on_room_redaction);
 
         info!("Started syncing…");
-        bot.client.sync(SyncSettings::new()).await.unwrap();
+        let session = Mutex::new(session);
+        bot.client
+            .sync_with_callback(SyncSettings::new(), |response| {
+                let mut session = session.lock().unwrap();
+                session.sync_token = Some(response.next_batch);
+                if let Err(err) = session.save(&state_dir) {
+                    warn!("Unable to store sync token: {}", err);
+                }
+                async { LoopCtrl::Continue }
+            })
+            .await
+            .unwrap();
+    }
+
+    /// Replays the reporting room events sent between the last sync of the previous run
+    /// and the initial sync, through the same handlers as the live events
+    async fn catch_up(&self, since: &str, until: &str) {
+        let mut events = Vec::new();
+        let mut from = Some(until.to_owned());
+        while let Some(token) = from {
+            let mut options = MessagesOptions::backward().from(token.as_str());
+            options.to = Some(since.to_owned());
+
+            match self.reporting_room.messages(options).await {
+                Ok(messages) if !messages.chunk.is_empty() => {
+                    events.extend(messages.chunk);
+                    from = messages.end;
+                }
+                Ok(_) => break,
+                Err(err) => {
+                    warn!("Unable to fetch missed events: {}", err);
+                    break;
+                }
+            }
+        }
+
+        if events.is_empty() {
+            return;
+        }
+        info!("Catching up on {} missed events…", events.len());
+        self.catching_up.store(true, Ordering::Relaxed);
+
+        // The events are paginated backwards, replay them in chronological order
+        let room = &self.reporting_room;
+        for event in events.iter().rev() {
+            let Ok(AnySyncTimelineEvent::MessageLike(event)) = event.raw().deserialize() else {
+                continue;
+            };
+
+            match event {
+                AnySyncMessageLikeEvent::RoomMessage(SyncMessageLikeEvent::Original(event)) => {
+                    Self::on_room_message(event, room.clone(), Ctx(self.clone())).await
+                }
+                AnySyncMessageLikeEvent::Reaction(SyncMessageLikeEvent::Original(event)) => {
+                    Self::on_room_reaction(event, room.clone(), Ctx(self.clone())).await
+                }
+                AnySyncMessageLikeEvent::RoomRedaction(event) => {
+                    Self::on_room_redaction(event, room.clone(), Ctx(self.clone())).await
+                }
+                _ => (),
+            }
+        }
+
+        self.catching_up.store(false, Ordering::Relaxed);
+    }
+
+    /// Restores the stored session, or logs in with the access token or password from the env
+    async fn connect(user: &UserId, state_dir: &Path) -> (Client, Session) {
+        let access_token = env::var("BOT_ACCESS_TOKEN").ok();
+
+        // A newly supplied access token replaces the stored session
+        let stored_session = Session::load(state_dir).filter(|session| {
+            access_token
+                .as_ref()
+                .is_none_or(|token| *token == session.matrix_session.tokens.access_token)
+        });
+        let device_id = stored_session
+            .as_ref()
+            .map(|session| session.matrix_session.meta.device_id.to_string());
+
+        if let Some(session) = stored_session {
+            info!("Restoring session…");
+            let client = Self::build_client(user, state_dir, Some(&session.homeserver_url)).await;
+            match Self::restore_session(&client, session.matrix_session.clone()).await {
+                Ok(()) => return (client, session),
+                Err(err) => warn!("Unable to restore session, logging in again: {}", err),
+            }
+        }
+
+        let homeserver_url = env::var("HOMESERVER_URL").ok();
+        let client = Self::build_client(user, state_dir, homeserver_url.as_deref()).await;
+        let matrix_session = match access_token {
+            Some(access_token) => {
+                let device_id = match env::var("BOT_DEVICE_ID") {
+                    Ok(device_id) => device_id.into(),
+                    Err(_) => Self::token_device_id(user, homeserver_url.as_deref(), &access_token)
+                        .await
+                        .expect("Unable to get the device of BOT_ACCESS_TOKEN, set BOT_DEVICE_ID"),
+                };
+                let matrix_session = MatrixSession {
+                    meta: SessionMeta {
+                        user_id: user.to_owned(),
+                        device_id,
+                    },
+                    tokens: SessionTokens {
+                        access_token,
+                        refresh_token: None,
+                    },
+                };
+
+                Self::restore_session(&client, matrix_session.clone())
+                    .await
+                    .expect("Unable to login with BOT_ACCESS_TOKEN");
+                matrix_session
+            }
+            None => {
+                let password =
+                    env::var("BOT_PASSWORD").expect("BOT_PASSWORD env variable not specified");
+                Self::login(&client, user.localpart(), &password, device_id.as_deref()).await
+            }
+        };
+
+        let session = Session::new(client.homeserver().to_string(), matrix_session);
+        if let Err(err) = session.save(state_dir) {
+            warn!("Unable to store session: {}", err);
+        }
+
+        (client, session)
+    }
+
+    async fn build_client(user: &UserId, state_dir: &Path, homeserver_url: Option<&str>) -> Client {
+        let server_name = ServerName::parse(user.server_name()).unwrap();
+        let request_config = RequestConfig::new().force_auth();
+        let store_passphrase = env::var("STATE_PASSPHRASE").ok();
+        let encryption_settings = EncryptionSettings {
+            auto_enable_cross_signing: true,
+            backup_download_strategy: BackupDownloadStrategy::AfterDecryptionFailure,
+            auto_enable_backups: true,
+        };
+
+        let mut client_builder = Client::builder()
+            .server_name(&server_name)
+            .request_config(request_config)
+            .sqlite_store(state_dir.join("matrix"), store_passphrase.as_deref())
+            .with_encryption_settings(encryption_settings);
+        if let Some(homeserver_url) = homeserver_url {
+            client_builder = client_builder.homeserver_url(homeserver_url);
+        }
+
+        client_builder.build().await.unwrap()
+    }
+
+    /// Restores the session, and checks that its access token is still valid
+    async fn restore_session(
+        client: &Client,
+        session: MatrixSession,
+    ) -> Result<(), matrix_sdk::Error> {
+        client.restore_session(session).await?;
+        let response = client.whoami().await?;
+        info!(
+            "Logged in as {}, using device_id {:?}",
+            response.user_id, response.device_id
+        );
+
+        Ok(())
+    }
+
+    /// Looks up the device of an access token. The state store belongs to a single device,
+    /// so this uses a temporary client without one.
+    async fn token_device_id(
+        user: &UserId,
+        homeserver_url: Option<&str>,
+        access_token: &str,
+    ) -> Option<OwnedDeviceId> {
+        let mut client_builder = Client::builder().server_name(user.server_name());
+        if let Some(homeserver_url) = homeserver_url {
+            client_builder = client_builder.homeserver_url(homeserver_url);
+        }
+        let client = client_builder.build().await.ok()?;
+
+        let session = MatrixSession {
+            meta: SessionMeta {
+                user_id: user.to_owned(),
+                device_id: "HEBBOT_WHOAMI".into(),
+            },
+            tokens: SessionTokens {
+                access_token: access_token.to_owned(),
+                refresh_token: None,
+            },
+        };
+        client.restore_session(session).await.ok()?;
+
+        match client.whoami().await {
+            Ok(response) => response.device_id,
+            Err(err) => {
+                warn!("Unable to get the device of the access token: {}", err);
+                None
+            }
+        }
     }
 
-    /// Login
-    async fn login(client: &Client, user: &str, pwd: &str) {
+    /// Login with password, reusing the device of previous logins so it keeps its encryption keys
+    async fn login(
+        client: &Client,
+        user: &str,
+        pwd: &str,
+        device_id: Option<&str>,
+    ) -> MatrixSession {
         info!("Logging in…");
-        let response = client
+        let mut login = client
             .matrix_auth()
             .login_username(user, pwd)
-            .initial_device_display_name("hebbot")
-            .await
-            .expect("Unable to login");
-
-        info!("Doing the initial sync…");
-        client
-            .sync_once(SyncSettings::new())
-            .await
-            .expect("Unable to sync");
+            .initial_device_display_name("hebbot");
+        if let Some(device_id) = device_id {
+            login = login.device_id(device_id);
+        }
+        let response = login.await.expect("Unable to login");
 
         info!(
             "Logged in as {}, got device_id {}",
             response.user_id, response.device_id
         );
+        MatrixSession::from(&response)
     }
 
     /// Simplified method for sending a matrix text/html message
@@ -182,21 +457,36 @@ impl Bot {
         }
     }
 
-    /// Simplified method for sending a file
-    async fn send_file(&self, url: OwnedMxcUri, filename: String, admin_room: bool) {
-        debug!("Send file (url: {:?}, admin-room: {:?})", url, admin_room);
+    /// Sends a notice about a news entry into the reporting room,
+    /// into the thread of the news entry if `use_threads` is enabled
+    async fn send_reporter_notice(&self, msg: &str, news_event_id: &EventId) {
+        if !self.config.use_threads {
+            self.send_message(msg, BotMsgType::ReportingRoomPlainNotice)
+                .await;
+            return;
+        }
 
-        let file_content = FileMessageEventContent::plain(filename, url);
-        let msgtype = MessageType::File(file_content);
-        let content = RoomMessageEventContent::new(msgtype);
+        debug!("Send thread message ({}): {}", news_event_id, msg);
+        let mut content = RoomMessageEventContent::notice_plain(msg);
+        content.relates_to = Some(Relation::Thread(Thread::plain(
+            news_event_id.to_owned(),
+            news_event_id.to_owned(),
+        )));
 
-        let room = if admin_room {
-            &self.admin_room
-        } else {
-            &self.reporting_room
-        };
+        self.reporting_room
+            .send(content)
+            .await
+            .expect("Unable to send message");
+    }
+
+    /// Simplified method for sending a file into the admin room, encrypted if the room is
+    async fn send_file(&self, filename: &str, mime_type: &mime::Mime, data: Vec<u8>) {
+        debug!("Send file ({}, {})", filename, mime_type);
 
-        room.send(content).await.expect("Unable to send file");
+        self.admin_room
+            .send_attachment(filename, mime_type, data, AttachmentConfig::new())
+            .await
+            .expect("Unable to send file");
     }
 
     /// Handling room messages events
@@ -210,8 +500,13 @@ impl Bot {
             if let Some(text) = event.text(false) {
                 // Reporting room
                 if room.room_id() == bot.reporting_room.room_id() {
-                    bot.on_reporting_room_msg_edit(text, edited_msg_event_id)
-                        .await;
+                    bot.on_reporting_room_msg_edit(
+                        text,
+                        event.html(false),
+                        event.mentions(),
+                        edited_msg_event_id,
+                    )
+                    .await;
                 }
             }
         }
@@ -221,14 +516,33 @@ impl Bot {
             let id = &event.event_id;
 
             // Reporting room
-            if room.room_id() == bot.reporting_room.room_id() {
-                bot.on_reporting_room_msg(text, event.content.mentions.as_ref(), &member, id)
-                    .await;
+            if room.room_id() == bot.reporting_room.room_id()
+                && !bot
+                    .on_reporting_room_follow_up(text, event.html(false), &member, &event)
+                    .await
+            {
+                let timestamp = event.origin_server_ts.to_system_time().unwrap().into();
+                bot.on_reporting_room_msg(
+                    text,
+                    event.html(false),
+                    event.mentions(),
+                    &member,
+                    id,
+                    timestamp,
+                )
+                .await;
             }
 
             // Admin room
             if room.room_id() == bot.admin_room.room_id() {
-                bot.on_admin_room_message(text, &member).await;
+                bot.on_admin_room_message(text, &member, id).await;
+            }
+        }
+        // Image or video
+        else if let Some(file) = MediaFile::from_message(&event) {
+            if room.room_id() == bot.reporting_room.room_id() {
+                let member = room.get_member(&event.sender).await.unwrap().unwrap();
+                bot.on_reporting_room_media(file, &member, &event).await;
             }
         }
     }
@@ -300,10 +614,16 @@ impl Bot {
     async fn on_reporting_room_msg(
         &self,
         message: &str,
+        html: Option<&str>,
         mentions: Option<&Mentions>,
         member: &RoomMember,
         event_id: &EventId,
+        timestamp: DateTime<Utc>,
     ) {
+        if self.news_store.lock().unwrap().contains_event(event_id) {
+            return;
+        }
+
         // We're going to ignore all messages, except if the mentions contain the ID of the bot,
         // or if the message mentions the bot by name at the beginning
         let bot_id = self.client.user_id().unwrap();
@@ -315,20 +635,85 @@ impl Bot {
         }
 
         // Create new news entry...
-        let news = News::new(event_id.to_owned(), member, message.to_owned());
+        let mentions = self.resolve_mentions(message, html, mentions).await;
+        let mut news = News::new(event_id.to_owned(), member, message.to_owned());
+        news.timestamp = timestamp;
+        news.set_message_html(html.map(str::to_owned));
+        news.set_mentions(mentions);
         self.add_news(news, true).await;
     }
 
+    /// New message in the thread of a news entry (if `use_threads` is enabled)
+    /// - Messages of the reporter get appended to the news entry as follow-up
+    /// - Returns `false` if the message isn't a follow-up
+    async fn on_reporting_room_follow_up(
+        &self,
+        message: &str,
+        html: Option<&str>,
+        member: &RoomMember,
+        event: &OriginalSyncRoomMessageEvent,
+    ) -> bool {
+        let Some(thread_root_id) = event.thread_root_id().filter(|_| self.config.use_threads)
+        else {
+            return false;
+        };
+
+        let bot_id = self.client.user_id().unwrap();
+        let bot_display_name = self.client.account().get_display_name().await.ok().unwrap();
+        let html = html.map(|html| utils::remove_bot_pill(bot_id, bot_display_name.clone(), html));
+        let message = utils::remove_bot_name(bot_id, bot_display_name, message);
+
+        let msg = {
+            let news_store = self.news_store.lock().unwrap();
+            let Some(news) = news_store
+                .news_by_message_id(thread_root_id)
+                .filter(|news| news.reporter_id == member.user_id())
+            else {
+                return false;
+            };
+
+            if news_store.contains_event(&event.event_id) {
+                return true;
+            }
+
+            news.add_follow_up(FollowUp::new(event.event_id.clone(), message, html));
+            let conflict = news.mark_edit_conflict();
+            let written = news_store.write_news(&news.event_id);
+
+            let msg = if conflict {
+                self.edit_conflict_message(news)
+            } else {
+                format!(
+                    "✅ {} added a follow-up message to their news entry. [{}]",
+                    news.reporter_id,
+                    self.message_link(&event.event_id)
+                )
+            };
+            stored_message(written, msg)
+        };
+
+        self.send_message(&msg, BotMsgType::AdminRoomHtmlNotice)
+            .await;
+        true
+    }
+
     /// New message in reporting room
     /// - When the bot gets mentioned at the beginning of the message,
     ///   the message will get stored as News in NewsStore
     async fn on_reporting_room_msg_edit(
         &self,
         updated_message: &str,
+        updated_html: Option<&str>,
+        updated_mentions: Option<&Mentions>,
         edited_msg_event_id: &EventId,
     ) {
+        let updated_mentions = self
+            .resolve_mentions(updated_message, updated_html, updated_mentions)
+            .await;
         let bot_id = self.client.user_id().unwrap();
         let bot_display_name = self.client.account().get_display_name().await.ok().unwrap();
+        let updated_html =
+            updated_html.map(|html| utils::remove_bot_pill(bot_id, bot_display_name.clone(), html));
         let updated_message = utils::remove_bot_name(bot_id, bot_display_name, updated_message);
         let link = self.message_link(edited_msg_event_id);
 
@@ -336,19 +721,41 @@ impl Bot {
             let news_store = self.news_store.lock().unwrap();
             let msg = if let Some(news) = news_store.news_by_message_id(edited_msg_event_id) {
                 news.set_message(updated_message);
-                if news.is_assigned() {
-                    Some(format!(
+                news.set_message_html(updated_html);
+                news.set_mentions(updated_mentions);
+                let conflict = news.mark_edit_conflict();
+                let written = news_store.write_news(&news.event_id);
+                let msg = if conflict {
+                    Some(self.edit_conflict_message(news))
+                } else {
+                    news.is_assigned().then(|| format!(
                         "✅ The news entry by {} got edited. Check the new text, and make sure you want to keep the assigned project/section. [{}]",
                         news.reporter_id,
                         link
                     ))
+                };
+                store_warning(written, msg)
+            } else if let Some(news) = news_store.news_by_follow_up_id(edited_msg_event_id) {
+                news.add_follow_up(FollowUp::new(
+                    edited_msg_event_id.to_owned(),
+                    updated_message,
+                    updated_html,
+                ));
+                let conflict = news.mark_edit_conflict();
+                let written = news_store.write_news(&news.event_id);
+                let msg = if conflict {
+                    Some(self.edit_conflict_message(news))
                 } else {
-                    None
-                }
+                    news.is_assigned().then(|| format!(
+                        "✅ A follow-up message of {}’s news entry got edited. Check the new text, and make sure you want to keep the assigned project/section. [{}]",
+                        news.reporter_id,
+                        link
+                    ))
+                };
+                store_warning(written, msg)
             } else {
                 None
             };
-            news_store.write_data();
             msg
         };
 
@@ -358,6 +765,49 @@ impl Bot {
         }
     }
 
+    /// New image / video in reporting room
+    /// - When it's a reply to a news entry or posted in its thread,
+    ///   it gets attached to that news entry (if `attach_replies` is enabled)
+    async fn on_reporting_room_media(
+        &self,
+        file: MediaFile,
+        member: &RoomMember,
+        event: &OriginalSyncRoomMessageEvent,
+    ) {
+        if !self.config.media.attach_replies {
+            return;
+        }
+
+        let reporter_id = {
+            let news_store = self.news_store.lock().unwrap();
+            if news_store.news_by_reaction_id(&event.event_id).is_some() {
+                return;
+            }
+
+            event
+                .thread_and_reply_ids()
+                .into_iter()
+                .find_map(|id| news_store.news_by_message_id(id))
+                .map(|news| news.reporter_id.clone())
+        };
+
+        // Only the reporter and editors can add files to a news entry
+        let Some(reporter_id) = reporter_id else {
+            return;
+        };
+        if reporter_id != event.sender
+            && !self
+                .can_react(member.user_id(), &ReactionType::Notice)
+                .await
+        {
+            return;
+        }
+
+        let message = self.attach_media(file, &event.event_id, event);
+        self.send_message(&message, BotMsgType::AdminRoomHtmlNotice)
+            .await;
+    }
+
     /// New emoji reaction in reporting room
     /// - Only reactions from editors are processed
     /// - "section emoji" -> add a news entry to a section (eg. "Interesting Projects")
@@ -372,26 +822,50 @@ impl Bot {
     ) {
         let reaction_emoji = reaction_emoji.strip_suffix(" ?").unwrap_or(reaction_emoji);
 
-        // Only allow editors to use general commands
-        // or the general public to use the notice emoji
-        let sender_is_hebbot = reaction_sender.user_id().as_str() == self.config.bot_user_id;
-        let sender_is_editor = self.is_editor(reaction_sender).await;
-        if sender_is_hebbot
-            || (self.config.restrict_notice
-                && !sender_is_editor
-                && !utils::emoji_cmp(reaction_emoji, &self.config.notice_emoji))
+        if self
+            .news_store
+            .lock()
+            .unwrap()
+            .contains_event(reaction_event_id)
         {
             return;
         }
 
+        if reaction_sender.user_id().as_str() == self.config.bot_user_id {
+            return;
+        }
+
+        // Only allow editors (or roles with the permission) to use general commands
+        // or the general public to use the notice emoji
+        let reaction_type = self.config.reaction_type_by_emoji(reaction_emoji);
+        let sender_is_permitted = self
+            .can_react(reaction_sender.user_id(), &reaction_type)
+            .await;
+        let sender_is_maintainer = self.is_maintainer_only(reaction_sender.user_id()).await;
+        let permission = reaction_type.permission(
+            sender_is_permitted,
+            sender_is_maintainer,
+            self.config.restrict_notice,
+        );
+        if permission == ReactionPermission::Denied {
+            return;
+        }
+
+        // Project maintainers are limited to the emoji of their projects
+        if permission == ReactionPermission::MaintainerOnly {
+            let msg = format!(
+                "⚠️ Ignored the {} reaction of {} on [{}], project maintainers can only use the emoji of their projects.",
+                reaction_emoji,
+                reaction_sender.user_id(),
+                self.message_link(&related_event.event_id)
+            );
+            self.send_message(&msg, BotMsgType::AdminRoomHtmlNotice)
+                .await;
+            return;
+        }
+
         let message: Option<String> = {
-            let reaction_type = self.config.reaction_type_by_emoji(reaction_emoji);
             let related_event_id = &related_event.event_id;
-            let related_event_timestamp: DateTime<Utc> = related_event
-                .origin_server_ts
-                .to_system_time()
-                .unwrap()
-                .into();
             let link = self.message_link(related_event_id);
 
             if reaction_type == ReactionType::None {
@@ -413,18 +887,28 @@ impl Bot {
                         .unwrap()
                         .unwrap();
 
-                    if !sender_is_editor
+                    if !sender_is_permitted
                         && (reaction_sender.user_id() != related_event_sender.user_id()
                             && self.config.restrict_notice)
                     {
                         return;
                     }
 
-                    let news = News::new(
+                    let mentions = self
+                        .resolve_mentions(text, related_event.html(true), related_event.mentions())
+                        .await;
+                    let mut news = News::new(
                         related_event_id.clone(),
                         &related_event_sender,
                         text.to_owned(),
                     );
+                    news.timestamp = related_event
+                        .origin_server_ts
+                        .to_system_time()
+                        .unwrap()
+                        .into();
+                    news.set_message_html(related_event.html(true).map(str::to_owned));
+                    news.set_mentions(mentions);
                     self.add_news(news, false).await;
                     None
                 }
@@ -452,13 +936,25 @@ impl Bot {
                             let project = project.unwrap();
                             news.add_project_name(reaction_event_id.to_owned(), project.name);
                             Some(format!(
-                                "✅ {} added the project description “{}” to {}’s news entry [{}].",
+                                "✅ {}{} added the project description “{}” to {}’s news entry [{}].",
                                 reaction_sender.user_id(),
+                                if sender_is_maintainer {
+                                    " (project maintainer)"
+                                } else {
+                                    ""
+                                },
                                 project.title,
                                 news.reporter_id,
                                 link
                             ))
                         }
+                        ReactionType::Review if sender_is_permitted => {
+                            news.add_approval(
+                                reaction_event_id.to_owned(),
+                                reaction_sender.user_id().to_owned(),
+                            );
+                            Some(self.approval_message(reaction_sender, news))
+                        }
                         _ => None,
                     }
                 } else {
@@ -471,89 +967,25 @@ impl Bot {
                         ))
                 }
             }
-            // Check if related message is an image
-            else if let Some(image) = related_event.image() {
+            // Check if related message is an image or video
+            else if related_event.image().is_some() || related_event.video().is_some() {
                 match reaction_type {
                     ReactionType::Notice => {
-                        let reporter_id = reaction_sender.user_id();
-                        let news_store = self.news_store.lock().unwrap();
-                        if let Some(news) = news_store.find_related_news(
-                            related_event.sender.as_ref(),
-                            &related_event_timestamp,
-                        ) {
-                            if !sender_is_editor
-                                && (reaction_sender.user_id() != related_event.sender
-                                    && self.config.restrict_notice)
-                            {
-                                return;
-                            }
-                            if let MediaSource::Plain(mxc_uri) = &image.source {
-                                news.add_image(
-                                    reaction_event_id.to_owned(),
-                                    related_event_id.clone(),
-                                    image.body.clone(),
-                                    mxc_uri.clone(),
-                                );
-                                Some(format!(
-                                    "✅ Added image to {}’s news entry (“{}”) [{}].",
-                                    news.reporter_id,
-                                    news.message_summary(),
-                                    link
-                                ))
-                            } else {
-                                None
-                            }
-                        } else {
-                            Some(format!(
-                                "❌ Unable to save {}’s image, no matching news entry found ({}).",
-                                reporter_id, link
-                            ))
+                        if !sender_is_permitted
+                            && (reaction_sender.user_id() != related_event.sender
+                                && self.config.restrict_notice)
+                        {
+                            return;
                         }
+
+                        let file = MediaFile::from_message(related_event);
+                        file.map(|file| self.attach_media(file, reaction_event_id, related_event))
                     }
                     _ => Some(format!(
-                        "❌ Invalid reaction emoji {} by {} for message type image [{}].",
+                        "❌ Invalid reaction emoji {} by {} for message type {} [{}].",
                         reaction_emoji,
                         reaction_sender.user_id(),
-                        link
-                    )),
-                }
-            }
-            // Check if related message is a video
-            else if let Some(video) = related_event.video() {
-                match reaction_type {
-                    ReactionType::Notice => {
-                        let reporter_id = reaction_sender.user_id();
-                        let news_store = self.news_store.lock().unwrap();
-                        if let Some(news) = news_store.find_related_news(
-                            related_event.sender.as_ref(),
-                            &related_event_timestamp,
-                        ) {
-                            if let MediaSource::Plain(mxc_uri) = &video.source {
-                                news.add_video(
-                                    reaction_event_id.to_owned(),
-                                    related_event_id.clone(),
-                                    video.body.clone(),
-                                    mxc_uri.clone(),
-                                );
-                                Some(format!(
-                                    "✅ Added video to {}’s news entry (“{}”) [{}].",
-                                    news.reporter_id,
-                                    news.message_summary(),
-                                    link
-                                ))
-                            } else {
-                                None
-                            }
-                        } else {
-                            Some(format!(
-                                "❌ Unable to save {}’s video, no matching news entry found ({}).",
-                                reporter_id, link
-                            ))
-                        }
-                    }
-                    _ => Some(format!(
-                        "❌ Invalid reaction emoji by {} for message type video [{}].",
-                        reaction_sender.user_id(),
+                        media_kind(related_event),
                         link
                     )),
                 }
@@ -567,15 +999,81 @@ impl Bot {
             }
         };
 
+        // Update stored news
+        let written = self
+            .news_store
+            .lock()
+            .unwrap()
+            .write_news(&related_event.event_id);
+
         // Send confirm message to admin room
-        if let Some(message) = message {
+        if let Some(message) = store_warning(written, message) {
             self.send_message(&message, BotMsgType::AdminRoomHtmlNotice)
                 .await;
         }
+    }
+
+    /// Adds an image / video to the news entry it belongs to.
+    /// If that's ambiguous, the editors get asked to attach it manually.
+    fn attach_media(
+        &self,
+        file: MediaFile,
+        key_event_id: &EventId,
+        file_event: &OriginalSyncRoomMessageEvent,
+    ) -> String {
+        let kind = media_kind(file_event);
+        let link = self.message_link(&file_event.event_id);
+        let timestamp: DateTime<Utc> = file_event.origin_server_ts.to_system_time().unwrap().into();
 
-        // Update stored news
         let news_store = self.news_store.lock().unwrap();
-        news_store.write_data();
+        match news_store.find_related_news(
+            &file_event.sender,
+            &timestamp,
+            &file_event.thread_and_reply_ids(),
+            self.config.media.association_window(),
+        ) {
+            RelatedNews::Found(news) => {
+                if file_event.image().is_some() {
+                    news.add_image(key_event_id.to_owned(), file);
+                } else {
+                    news.add_video(key_event_id.to_owned(), file);
+                }
+                let written = news_store.write_news(&news.event_id);
+                stored_message(
+                    written,
+                    format!(
+                        "✅ Added {} to {}’s news entry (“{}”) [{}].",
+                        kind,
+                        news.reporter_id,
+                        news.message_summary(),
+                        link
+                    ),
+                )
+            }
+            RelatedNews::Ambiguous(candidates) => {
+                let mut msg = format!(
+                    "⚠️ {}’s {} [{}] could belong to multiple news entries. \
+                    Use one of these commands to attach it:<br>",
+                    file_event.sender, kind, link
+                );
+                for news in candidates {
+                    write!(
+                        msg,
+                        "- “{}” [{}]: <code>!attach {} {}</code><br>",
+                        news.message_summary(),
+                        self.message_link(&news.event_id),
+                        file.event_id,
+                        news.event_id
+                    )
+                    .unwrap();
+                }
+                msg
+            }
+            RelatedNews::None => format!(
+                "❌ Unable to save {}’s {}, no matching news entry found ({}).",
+                file_event.sender, kind, link
+            ),
+        }
     }
 
     /// Something got redacted in reporting room
@@ -587,24 +1085,47 @@ impl Bot {
             let link = self.message_link(redacted_event_id);
 
             // Redaction / deletion of the news entry itself
-            let msg = if let Ok(news) = news_store.remove_news(redacted_event_id) {
-                Some(format!(
-                    "✅ {}’s news entry got deleted by {}.",
-                    news.reporter_id,
-                    member.user_id()
+            let msg = if news_store.news_by_message_id(redacted_event_id).is_some() {
+                match news_store.remove_news(redacted_event_id) {
+                    Ok(news) => Some(format!(
+                        "✅ {}’s news entry got deleted by {}.",
+                        news.reporter_id,
+                        member.user_id()
+                    )),
+                    Err(err) => Some(format!(
+                        "❌ Unable to remove the deleted news entry [{}] from the news store: {}",
+                        link, err
+                    )),
+                }
+            // A follow-up message got redacted / deleted
+            } else if let Some(news) = news_store.news_by_follow_up_id(redacted_event_id) {
+                news.remove_follow_up(redacted_event_id);
+                let written = news_store.write_news(&news.event_id);
+                Some(stored_message(
+                    written,
+                    format!(
+                        "✅ {} deleted a follow-up message of {}’s news entry.",
+                        member.user_id(),
+                        news.reporter_id,
+                    ),
                 ))
             // An image / video got redacted / deleted
             } else if let Some(news) = news_store.news_by_file_id(redacted_event_id) {
-                news.remove_file(&redacted_event_id.to_owned());
-                Some(format!(
-                    "✅ {} deleted an image/video of {}’s news entry.",
-                    member.user_id(),
-                    news.reporter_id,
+                news.remove_file(redacted_event_id);
+                let written = news_store.write_news(&news.event_id);
+                Some(stored_message(
+                    written,
+                    format!(
+                        "✅ {} deleted an image/video of {}’s news entry.",
+                        member.user_id(),
+                        news.reporter_id,
+                    ),
                 ))
             // Redaction of reaction events (project / section)
             } else if let Some(news) = news_store.news_by_reaction_id(redacted_event_id) {
                 let reaction_type = news.remove_reaction_id(redacted_event_id);
-                if reaction_type == ReactionType::Notice {
+                let written = news_store.write_news(&news.event_id);
+                let msg = if reaction_type == ReactionType::Notice {
                     Some(format!(
                         "✅ {} removed their image/video notice reaction from {}’s news entry. [{}]",
                         member.user_id(),
@@ -625,12 +1146,12 @@ impl Bot {
                         redacted_event_id
                     );
                     None
-                }
+                };
+                store_warning(written, msg)
             } else {
                 None
             };
 
-            news_store.write_data();
             msg
         };
 
@@ -643,7 +1164,7 @@ impl Bot {
 
     /// New message in admin room
     /// This is just for administrative stuff (eg. commands)
-    async fn on_admin_room_message(&self, msg: &str, member: &RoomMember) {
+    async fn on_admin_room_message(&self, msg: &str, member: &RoomMember, event_id: &EventId) {
         let msg = msg.trim();
 
         // Check if the message is a command
@@ -651,14 +1172,6 @@ impl Bot {
             return;
         }
 
-        // Check if the sender is a editor (= has the permission to use commands)
-        if !self.is_editor(member).await {
-            let msg = "You don’t have the permission to use commands.";
-            self.send_message(msg, BotMsgType::AdminRoomPlainNotice)
-                .await;
-            return;
-        }
-
         // Parse command and optional args
         let mut split: Vec<&str> = msg.splitn(2, ' ').collect();
         let args = if split.len() == 2 {
@@ -669,20 +1182,47 @@ impl Bot {
         let command = split.pop().unwrap_or("");
         let command = command.trim();
 
-        info!("Received command: {} ({})", command, args);
-
-        match command {
+        // Check if the sender has the permission to use the command
+        if !self.can_use_command(member.user_id(), command).await {
+            let msg = if self.config.roles_of(member.user_id()).next().is_some() {
+                format!("You don’t have the permission to use {}.", command)
+            } else {
+                "You don’t have the permission to use commands.".to_string()
+            };
+            self.send_message(&msg, BotMsgType::AdminRoomPlainNotice)
+                .await;
+            return;
+        }
+
+        info!("Received command: {} ({})", command, args);
+
+        match command {
             "!about" => self.about_command().await,
-            "!clear" => self.clear_command().await,
+            "!approve" => self.approve_command(member, event_id, args).await,
+            "!assign" => self.assign_command(member, event_id, args).await,
+            "!attach" => self.attach_command(args).await,
+            "!clear" => self.clear_command(member).await,
             "!details" => self.details_command(args).await,
+            "!edit" => self.edit_command(member, args).await,
+            "!editions" => self.editions_command().await,
             "!help" => self.help_command().await,
             "!list-config" => self.list_config_command().await,
             "!list-projects" => self.list_projects_command().await,
             "!list-sections" => self.list_sections_command().await,
-            "!render" => self.render_command(member).await,
+            "!move" => self.move_command(member, event_id, args).await,
+            "!order" => self.order_command(member, args).await,
+            "!pin" => self.pin_command(member, args).await,
+            "!publish" => self.publish_command(member).await,
+            "!render" => self.render_command(member, args).await,
+            "!rerender" => self.rerender_command(args).await,
+            "!reply" => self.reply_command(member, args).await,
             "!restart" => self.restart_command().await,
+            "!resync" => self.resync_command().await,
             "!say" => self.say_command(args).await,
+            "!show-edition" => self.show_edition_command(args).await,
             "!status" => self.status_command().await,
+            "!unapprove" => self.unapprove_command(member, args).await,
+            "!unassign" => self.unassign_command(member, args).await,
             "!update-config" => self.update_config_command().await,
             _ => self.unrecognized_command().await,
         }
@@ -691,16 +1231,32 @@ impl Bot {
     async fn help_command(&self) {
         let help = "Available commands: \n\n\
             !about \n\
+            !approve <news> \n\
+            !assign <news> <project|section> \n\
+            !attach <file event id> <news> \n\
             !clear \n\
             !details <name> \n\
+            !edit <news> [text] \n\
+            !editions \n\
             !list-config \n\
             !list-projects \n\
             !list-sections \n\
-            !render \n\
+            !move <news> <section> \n\
+            !order <news> [position] \n\
+            !pin <news> \n\
+            !publish \n\
+            !render [output] \n\
+            !rerender <number> [output] \n\
+            !reply <news> <message> \n\
             !restart \n\
+            !resync \n\
             !say <message> \n\
+            !show-edition <number> \n\
             !status \n\
-            !update-config";
+            !unapprove <news> \n\
+            !unassign <news> [project|section] \n\
+            !update-config \n\n\
+            <news> is the number shown by !status, or the event id of the news entry";
 
         self.send_message(help, BotMsgType::AdminRoomPlainNotice)
             .await;
@@ -718,14 +1274,173 @@ impl Bot {
             .await;
     }
 
-    async fn clear_command(&self) {
+    /// Approves a news entry, like the review emoji.
+    /// The approval is keyed by the event id of the command message.
+    async fn approve_command(&self, editor: &RoomMember, event_id: &EventId, news_ref: &str) {
+        if !self
+            .can_react(editor.user_id(), &ReactionType::Review)
+            .await
+        {
+            let msg = "❌ You don’t have the permission to approve news entries.";
+            self.send_message(msg, BotMsgType::AdminRoomPlainNotice)
+                .await;
+            return;
+        }
+
+        let msg = {
+            let news_store = self.news_store.lock().unwrap();
+            match news_store.news_by_ref(news_ref) {
+                _ if !self.config.review.enabled() => {
+                    "❌ The review workflow isn’t enabled (see <code>required_approvals</code>)."
+                        .to_string()
+                }
+                Some(news) => {
+                    news.add_approval(event_id.to_owned(), editor.user_id().to_owned());
+                    let written = news_store.write_news(&news.event_id);
+                    stored_message(written, self.approval_message(editor, news))
+                }
+                None if !news_ref.trim().is_empty() => {
+                    format!("❌ Unable to find the news entry {}.", news_ref.trim())
+                }
+                None => "❌ Usage: !approve <news>".to_string(),
+            }
+        };
+
+        self.send_message(&msg, BotMsgType::AdminRoomHtmlNotice)
+            .await;
+    }
+
+    /// Assigns a news entry to a project or section, like the corresponding reaction.
+    /// The assignment is keyed by the event id of the command message.
+    async fn assign_command(&self, editor: &RoomMember, event_id: &EventId, args: &str) {
+        let (news_ref, term) = args.trim().split_once(' ').unwrap_or((args.trim(), ""));
+        if !term.trim().is_empty() && !self.can_assign(editor, term.trim()).await {
+            return;
+        }
+
+        let msg = {
+            let news_store = self.news_store.lock().unwrap();
+            match (
+                news_store.news_by_ref(news_ref),
+                self.config.reaction_type_by_term(term.trim()),
+            ) {
+                (Some(news), ReactionType::Section(Some(section))) => {
+                    news.add_section_name(event_id.to_owned(), section.name);
+                    let written = news_store.write_news(&news.event_id);
+                    stored_message(
+                        written,
+                        format!(
+                            "✅ {} added {}’s news entry [{}] to the “{}” section.",
+                            editor.user_id(),
+                            news.reporter_id,
+                            self.message_link(&news.event_id),
+                            section.title
+                        ),
+                    )
+                }
+                (Some(news), ReactionType::Project(Some(project))) => {
+                    news.add_project_name(event_id.to_owned(), project.name);
+                    let written = news_store.write_news(&news.event_id);
+                    stored_message(
+                        written,
+                        format!(
+                            "✅ {} added the project description “{}” to {}’s news entry [{}].",
+                            editor.user_id(),
+                            project.title,
+                            news.reporter_id,
+                            self.message_link(&news.event_id)
+                        ),
+                    )
+                }
+                (None, _) if !news_ref.is_empty() => {
+                    format!("❌ Unable to find the news entry {}.", news_ref)
+                }
+                (Some(_), _) if !term.trim().is_empty() => format!(
+                    "❌ Unable to find a project or section named {}.",
+                    term.trim()
+                ),
+                _ => "❌ Usage: !assign <news> <project|section>".to_string(),
+            }
+        };
+
+        self.send_message(&msg, BotMsgType::AdminRoomHtmlNotice)
+            .await;
+    }
+
+    /// Attaches an image / video to the given news entry, replacing an earlier association
+    async fn attach_command(&self, args: &str) {
+        let args = match args.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [file_event_id, news_ref] => EventId::parse(file_event_id)
+                .ok()
+                .map(|id| (id, news_ref.to_string())),
+            _ => None,
+        };
+        let Some((file_event_id, news_ref)) = args else {
+            let msg = "❌ Usage: !attach <file event id> <news>";
+            self.send_message(msg, BotMsgType::AdminRoomPlainNotice)
+                .await;
+            return;
+        };
+
+        let event = utils::room_event_by_id(&self.reporting_room, &file_event_id).await;
+        let file_event = event.as_ref().and_then(utils::as_message_event);
+        let file = file_event.and_then(MediaFile::from_message);
+
+        let msg = {
+            let news_store = self.news_store.lock().unwrap();
+            match (file, file_event, news_store.news_by_ref(&news_ref)) {
+                (Some(file), Some(file_event), Some(news)) => {
+                    let mut written = Ok(());
+                    if let Some(previous) = news_store.news_by_file_id(&file_event_id) {
+                        previous.remove_file(&file_event_id);
+                        written = news_store.write_news(&previous.event_id);
+                    }
+
+                    // There's no reaction event, the file is keyed by its own event id
+                    if file_event.image().is_some() {
+                        news.add_image(file_event_id.clone(), file);
+                    } else {
+                        news.add_video(file_event_id.clone(), file);
+                    }
+                    let written = written.and(news_store.write_news(&news.event_id));
+
+                    stored_message(
+                        written,
+                        format!(
+                            "✅ Attached the {} to {}’s news entry (“{}”) [{}].",
+                            media_kind(file_event),
+                            news.reporter_id,
+                            news.message_summary(),
+                            self.message_link(&news.event_id)
+                        ),
+                    )
+                }
+                (None, _, _) | (_, None, _) => format!(
+                    "❌ Unable to find an image or video with ID {}.",
+                    file_event_id
+                ),
+                (_, _, None) => format!("❌ Unable to find the news entry {}.", news_ref),
+            }
+        };
+
+        self.send_message(&msg, BotMsgType::AdminRoomHtmlNotice)
+            .await;
+    }
+
+    async fn clear_command(&self, member: &RoomMember) {
         let msg = {
             let mut news_store = self.news_store.lock().unwrap();
 
             let news = news_store.news();
-            news_store.clear_news();
-
-            format!("✅ Cleared {} news entries!", news.len())
+            match news_store.archive_news(member.user_id(), member.name()) {
+                Ok(Some(number)) => format!(
+                    "✅ Cleared {} news entries, and archived them in edition #{}!",
+                    news.len(),
+                    number
+                ),
+                Ok(None) => "✅ Cleared 0 news entries!".to_string(),
+                Err(err) => format!("❌ Unable to archive the news entries: {}", err),
+            }
         };
 
         self.send_message(&msg, BotMsgType::AdminRoomPlainNotice)
@@ -735,18 +1450,88 @@ impl Bot {
     async fn details_command(&self, term: &str) {
         let result_project = self.config.project_by_name(term);
         let result_section = self.config.section_by_name(term);
+        let result_output = self.config.output_by_name(term);
         let result_reaction = self.config.reaction_type_by_emoji(term);
 
         let msg = if let Some(project) = result_project {
             project.html_details()
         } else if let Some(section) = result_section {
             section.html_details()
+        } else if let Some(output) = result_output {
+            output.html_details()
         } else {
             match result_reaction {
                 ReactionType::Section(section) => section.unwrap().html_details(),
                 ReactionType::Project(project) => project.unwrap().html_details(),
                 ReactionType::None => format!("❌ Unable to find details for ”{}”.", term),
                 ReactionType::Notice => format!("{} is configured as notice emoji", term),
+                ReactionType::Review => format!("{} is configured as review emoji", term),
+            }
+        };
+
+        self.send_message(&msg, BotMsgType::AdminRoomHtmlNotice)
+            .await;
+    }
+
+    /// Sets the text of a news entry, which gets rendered instead of the reporter's message.
+    /// Without text, the reporter's message is used again.
+    async fn edit_command(&self, editor: &RoomMember, args: &str) {
+        let args = args.trim();
+        let (news_ref, text) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+        let text = text.trim();
+
+        let msg = {
+            let news_store = self.news_store.lock().unwrap();
+            match news_store.news_by_ref(news_ref) {
+                Some(news) if text.is_empty() => {
+                    news.set_editor_edit(None);
+                    let written = news_store.write_news(&news.event_id);
+                    stored_message(written, format!(
+                        "✅ {} removed the edited text of {}’s news entry [{}], the reporter’s message is used again.",
+                        editor.user_id(),
+                        news.reporter_id,
+                        self.message_link(&news.event_id)
+                    ))
+                }
+                Some(news) => {
+                    let editor_edit = EditorEdit::new(editor.user_id().to_owned(), text.to_owned());
+                    news.set_editor_edit(Some(editor_edit));
+                    let written = news_store.write_news(&news.event_id);
+                    stored_message(
+                        written,
+                        format!(
+                            "✅ {} edited the text of {}’s news entry [{}].",
+                            editor.user_id(),
+                            news.reporter_id,
+                            self.message_link(&news.event_id)
+                        ),
+                    )
+                }
+                None if !news_ref.is_empty() => {
+                    format!("❌ Unable to find the news entry {}.", news_ref)
+                }
+                None => "❌ Usage: !edit <news> [text]".to_string(),
+            }
+        };
+
+        self.send_message(&msg, BotMsgType::AdminRoomHtmlNotice)
+            .await;
+    }
+
+    async fn editions_command(&self) {
+        let msg = {
+            let news_store = self.news_store.lock().unwrap();
+            let editions = news_store.editions();
+
+            let mut list = String::new();
+            for edition in editions {
+                write!(list, "- {} <br>", edition.html_summary()).unwrap();
+            }
+
+            if editions.is_empty() {
+                "No editions archived yet. Use !publish to create one.".to_string()
+            } else {
+                format!("{} archived editions: <br>{}", editions.len(), list)
             }
         };
 
@@ -794,85 +1579,490 @@ impl Bot {
             .await;
     }
 
-    async fn render_command(&self, editor: &RoomMember) {
-        let result = {
+    /// Moves a news entry into the given section, replacing its other section assignments
+    async fn move_command(&self, editor: &RoomMember, event_id: &EventId, args: &str) {
+        let (news_ref, term) = args.trim().split_once(' ').unwrap_or((args.trim(), ""));
+        if !term.trim().is_empty() && !self.can_assign(editor, term.trim()).await {
+            return;
+        }
+
+        let msg = {
             let news_store = self.news_store.lock().unwrap();
-            let news = news_store.news();
-            let config = self.config.clone();
+            match (
+                news_store.news_by_ref(news_ref),
+                self.config.reaction_type_by_term(term.trim()),
+            ) {
+                (Some(news), ReactionType::Section(Some(section))) => {
+                    news.remove_section_name(None);
+                    news.add_section_name(event_id.to_owned(), section.name);
+                    let written = news_store.write_news(&news.event_id);
+                    stored_message(
+                        written,
+                        format!(
+                            "✅ {} moved {}’s news entry [{}] to the “{}” section.",
+                            editor.user_id(),
+                            news.reporter_id,
+                            self.message_link(&news.event_id),
+                            section.title
+                        ),
+                    )
+                }
+                (None, _) if !news_ref.is_empty() => {
+                    format!("❌ Unable to find the news entry {}.", news_ref)
+                }
+                (Some(_), _) if !term.trim().is_empty() => {
+                    format!("❌ Unable to find a section named {}.", term.trim())
+                }
+                _ => "❌ Usage: !move <news> <section>".to_string(),
+            }
+        };
+
+        self.send_message(&msg, BotMsgType::AdminRoomHtmlNotice)
+            .await;
+    }
+
+    /// Moves a news entry to the given position within its section / project,
+    /// without position it's ordered by submission time again
+    async fn order_command(&self, editor: &RoomMember, args: &str) {
+        let args = args.trim();
+        let (news_ref, position) = args.split_once(' ').unwrap_or((args, ""));
+        let position = position.trim();
 
-            render::render(news, config, editor)
+        let msg = {
+            let news_store = self.news_store.lock().unwrap();
+            match (news_store.news_by_ref(news_ref), position.parse::<u32>()) {
+                (Some(news), _) if position.is_empty() => {
+                    news.set_position(None);
+                    let written = news_store.write_news(&news.event_id);
+                    stored_message(written, format!(
+                        "✅ {} reset the position of {}’s news entry [{}], it’s ordered by submission time again.",
+                        editor.user_id(),
+                        news.reporter_id,
+                        self.message_link(&news.event_id)
+                    ))
+                }
+                (Some(news), Ok(position)) if position > 0 => {
+                    news.set_position(Some(position));
+                    let written = news_store.write_news(&news.event_id);
+                    stored_message(written, format!(
+                        "✅ {} moved {}’s news entry [{}] to position {} within its section/project.",
+                        editor.user_id(),
+                        news.reporter_id,
+                        self.message_link(&news.event_id),
+                        position
+                    ))
+                }
+                (None, _) if !news_ref.is_empty() => {
+                    format!("❌ Unable to find the news entry {}.", news_ref)
+                }
+                _ => "❌ Usage: !order <news> [position]".to_string(),
+            }
         };
-        let result = match result {
-            Ok(result) => result,
-            Err(error) => {
-                let msg = format!("❌ Could not render template: <pre>{}</pre>", error);
-                self.send_message(&msg, BotMsgType::AdminRoomHtmlNotice)
-                    .await;
-                return;
+
+        self.send_message(&msg, BotMsgType::AdminRoomHtmlNotice)
+            .await;
+    }
+
+    /// Pins a news entry to the top of its section / project, or unpins it again
+    async fn pin_command(&self, editor: &RoomMember, news_ref: &str) {
+        let msg = {
+            let news_store = self.news_store.lock().unwrap();
+            match news_store.news_by_ref(news_ref) {
+                Some(news) => {
+                    news.set_pinned(!news.is_pinned());
+                    let written = news_store.write_news(&news.event_id);
+                    stored_message(
+                        written,
+                        format!(
+                            "✅ {} {} {}’s news entry [{}].",
+                            editor.user_id(),
+                            if news.is_pinned() {
+                                "pinned"
+                            } else {
+                                "unpinned"
+                            },
+                            news.reporter_id,
+                            self.message_link(&news.event_id)
+                        ),
+                    )
+                }
+                None if !news_ref.trim().is_empty() => {
+                    format!("❌ Unable to find the news entry {}.", news_ref.trim())
+                }
+                None => "❌ Usage: !pin <news>".to_string(),
             }
         };
 
-        // Upload rendered content as markdown file
-        let bytes = result.rendered.into_bytes();
-        let response = self
-            .client
-            .media()
-            .upload(&mime::TEXT_PLAIN_UTF_8, bytes, None)
+        self.send_message(&msg, BotMsgType::AdminRoomHtmlNotice)
+            .await;
+    }
+
+    async fn publish_command(&self, editor: &RoomMember) {
+        let news = self.news_store.lock().unwrap().news();
+        let outputs = self.config.outputs();
+        let Some(rendered) = self
+            .render_news(news, &outputs, editor.name(), Utc::now())
             .await
-            .expect("Can't upload rendered file.");
+        else {
+            return;
+        };
+
+        let result = self.news_store.lock().unwrap().publish_edition(
+            editor.user_id(),
+            editor.name(),
+            rendered,
+        );
 
-        // Send file
-        self.send_file(response.content_uri, "rendered.md".to_string(), true)
+        let msg = match result {
+            Ok(number) => format!(
+                "✅ Published edition #{}. Use !clear to start with the next edition.",
+                number
+            ),
+            Err(err) => format!("❌ Unable to store the published edition: {}", err),
+        };
+        self.send_message(&msg, BotMsgType::AdminRoomPlainNotice)
             .await;
+    }
 
-        // Send warnings
-        let warnings = utils::format_messages(true, &result.warnings);
-        if !result.warnings.is_empty() {
-            self.send_message(&warnings, BotMsgType::AdminRoomHtmlNotice)
-                .await;
-        }
+    async fn render_command(&self, editor: &RoomMember, args: &str) {
+        let Some(outputs) = self.outputs_by_arg(args).await else {
+            return;
+        };
 
-        // Send notes
-        let notes = utils::format_messages(false, &result.notes);
-        if !result.notes.is_empty() {
-            self.send_message(&notes, BotMsgType::AdminRoomHtmlNotice)
+        let news = self.news_store.lock().unwrap().news();
+        self.render_news(news, &outputs, editor.name(), Utc::now())
+            .await;
+    }
+
+    async fn rerender_command(&self, args: &str) {
+        let (number, output_name) = args.trim().split_once(' ').unwrap_or((args, ""));
+        let Some(edition) = self.edition_by_arg(number).await else {
+            return;
+        };
+        let Some(outputs) = self.outputs_by_arg(output_name).await else {
+            return;
+        };
+
+        self.render_news(
+            edition.news,
+            &outputs,
+            &edition.editor_display_name,
+            edition.timestamp,
+        )
+        .await;
+    }
+
+    /// Sends editor feedback to the reporter of a news entry
+    async fn reply_command(&self, editor: &RoomMember, args: &str) {
+        let (news_ref, feedback) = args.trim().split_once(' ').unwrap_or((args.trim(), ""));
+        let news_event_id = self
+            .news_store
+            .lock()
+            .unwrap()
+            .news_by_ref(news_ref)
+            .map(|news| news.event_id.clone());
+
+        let Some(news_event_id) = news_event_id.filter(|_| !feedback.trim().is_empty()) else {
+            let msg = "❌ Usage: !reply <news> <message>";
+            self.send_message(msg, BotMsgType::AdminRoomPlainNotice)
                 .await;
+            return;
+        };
+
+        let msg = format!("{}: {}", editor.name(), feedback.trim());
+        self.send_reporter_notice(&msg, &news_event_id).await;
+    }
+
+    async fn restart_command(&self) {
+        self.send_message("Restarting hebbot…", BotMsgType::AdminRoomPlainNotice)
+            .await;
+        let _ = Command::new("/proc/self/exe").exec();
+    }
+
+    /// Rebuilds the assignments and media of all news entries from the reporting room,
+    /// e.g. after missed redactions or reactions of removed editors
+    async fn resync_command(&self) {
+        self.send_message(
+            "Resyncing news entries with the reporting room…",
+            BotMsgType::AdminRoomPlainNotice,
+        )
+        .await;
+
+        let event_ids: Vec<OwnedEventId> = self
+            .news_store
+            .lock()
+            .unwrap()
+            .news()
+            .into_iter()
+            .map(|news| news.event_id)
+            .collect();
+
+        let mut changes = String::new();
+        for event_id in event_ids {
+            if let Some(change) = self.resync_news(&event_id).await {
+                write!(changes, "- {} <br>", change).unwrap();
+            }
         }
 
-        // Generate a curl command which can get used to download all files (images/videos).
-        let mut files = result.images.clone();
-        files.append(&mut result.videos.clone());
-        if !files.is_empty() {
-            self.send_message(
-                "Use this command to download all files:",
-                BotMsgType::AdminRoomHtmlNotice,
-            )
+        let msg = if changes.is_empty() {
+            "✅ Resync finished, all news entries are up to date.".to_string()
+        } else {
+            format!("✅ Resync finished, changed news entries: <br>{}", changes)
+        };
+        self.send_message(&msg, BotMsgType::AdminRoomHtmlNotice)
             .await;
+    }
 
-            let mut curl_command = "curl".to_string();
-            for (filename, uri) in &files {
-                if uri.is_valid() {
-                    let url = format!(
-                        "{}_matrix/media/r0/download/{}/{}",
-                        self.client.homeserver(),
-                        uri.server_name().unwrap(),
-                        uri.media_id().unwrap()
-                    );
+    /// Rebuilds a news entry from its message and the related events,
+    /// returns a summary of the changes.
+    /// The entry stays untouched if any of the events can't be fetched.
+    async fn resync_news(&self, event_id: &EventId) -> Option<String> {
+        let link = self.message_link(event_id);
+        let skipped = |err: Error| {
+            Some(format!(
+                "⚠️ Unable to fetch the related events ({}), skipped [{}]",
+                err, link
+            ))
+        };
+
+        let event = match utils::fetch_room_event(&self.reporting_room, event_id).await {
+            Ok(Some(event)) => event,
+            Ok(None) => {
+                return Some(format!(
+                    "⚠️ Unable to fetch the message, skipped [{}]",
+                    link
+                ))
+            }
+            Err(err) => return skipped(err),
+        };
+
+        // The message got deleted
+        let message_event = utils::as_message_event(&event);
+        let Some(text) = message_event.and_then(|event| event.text(true)) else {
+            let news = match self.news_store.lock().unwrap().remove_news(event_id) {
+                Ok(news) => news,
+                Err(Error::NewsEventIdNotFound) => return None,
+                Err(err) => {
+                    return Some(format!(
+                        "❌ Unable to remove the deleted news entry [{}] from the news store: {}",
+                        link, err
+                    ))
+                }
+            };
+            return Some(format!(
+                "{}’s news entry got deleted [{}]",
+                news.reporter_id, link
+            ));
+        };
 
-                    write!(curl_command, " {} -o {}", url, filename).unwrap();
+        // The latest edit of the message
+        let html = message_event.and_then(|event| event.html(true));
+        let mentions = message_event.and_then(|event| event.mentions());
+        let mentions = self.resolve_mentions(text, html, mentions).await;
+        let bot_id = self.client.user_id().unwrap();
+        let bot_display_name = self.client.account().get_display_name().await.ok().unwrap();
+        let html = html.map(|html| utils::remove_bot_pill(bot_id, bot_display_name.clone(), html));
+        let text = utils::remove_bot_name(bot_id, bot_display_name, text);
+
+        // Project / section reactions and approvals, with the same permissions
+        // as when reacting while the bot is running
+        let all_reactions = match self.reactions(event_id).await {
+            Ok(reactions) => reactions,
+            Err(err) => return skipped(err),
+        };
+        let mut reactions = Vec::new();
+        for (reaction_id, sender, emoji) in &all_reactions {
+            let reaction_type = self.config.reaction_type_by_emoji(emoji);
+            if self.reaction_permission(sender, &reaction_type).await == ReactionPermission::Allowed
+            {
+                reactions.push((reaction_id.clone(), sender.clone(), reaction_type));
+            }
+        }
+
+        let news = self
+            .news_store
+            .lock()
+            .unwrap()
+            .news_by_message_id(event_id)?
+            .clone();
+
+        // Assignments made with admin commands are keyed by the command message
+        let mut command_ids = Vec::new();
+        let stored_ids = news
+            .section_reactions()
+            .into_keys()
+            .chain(news.project_reactions().into_keys())
+            .chain(news.approval_reactions().into_keys());
+        for key_event_id in stored_ids {
+            if all_reactions.iter().any(|(id, _, _)| *id == key_event_id) {
+                continue;
+            }
+            match self.is_admin_room_event(&key_event_id).await {
+                Ok(true) => command_ids.push(key_event_id),
+                Ok(false) => (),
+                Err(err) => return skipped(err),
+            }
+        }
+
+        // Images / videos which are still there, and still have a valid notice reaction
+        let mut images = HashMap::new();
+        let mut videos = HashMap::new();
+        for (files, is_image) in [
+            (news.image_reactions(), true),
+            (news.video_reactions(), false),
+        ] {
+            let mut file_ids: Vec<&OwnedEventId> = files.values().map(|f| &f.event_id).collect();
+            file_ids.sort();
+            file_ids.dedup();
+
+            for file_id in file_ids {
+                let kept = match self.resync_file(file_id, &files).await {
+                    Ok(kept) => kept,
+                    Err(err) => return skipped(err),
+                };
+                if is_image {
+                    images.extend(kept);
+                } else {
+                    videos.extend(kept);
                 }
             }
+        }
+
+        let news_store = self.news_store.lock().unwrap();
+        let news = news_store.news_by_message_id(event_id)?;
+        let mut changes = Vec::new();
+        if news.original_message() != text || news.original_message_html() != html {
+            news.set_message(text);
+            news.set_message_html(html);
+            if news.mark_edit_conflict() {
+                changes.push("text updated (differs from the text set with !edit)".to_string());
+            } else {
+                changes.push("text updated".to_string());
+            }
+        }
+        news.set_mentions(mentions);
+
+        let before = (
+            news.section_names(),
+            news.project_names(),
+            news.image_files().len(),
+            news.video_files().len(),
+            news.approvers(),
+        );
+        news.resync_assignments(&reactions, &command_ids);
+        news.set_files(images, videos);
+
+        if before.0 != news.section_names() {
+            changes.push(format!(
+                "sections: {} → {}",
+                names_or_none(&before.0),
+                names_or_none(&news.section_names())
+            ));
+        }
+        if before.1 != news.project_names() {
+            changes.push(format!(
+                "projects: {} → {}",
+                names_or_none(&before.1),
+                names_or_none(&news.project_names())
+            ));
+        }
+        if before.2 != news.image_files().len() {
+            changes.push(format!(
+                "images: {} → {}",
+                before.2,
+                news.image_files().len()
+            ));
+        }
+        if before.3 != news.video_files().len() {
+            changes.push(format!(
+                "videos: {} → {}",
+                before.3,
+                news.video_files().len()
+            ));
+        }
+        if before.4 != news.approvers() {
+            let approvers = |ids: &[OwnedUserId]| {
+                names_or_none(&ids.iter().map(|id| id.to_string()).collect::<Vec<_>>())
+            };
+            changes.push(format!(
+                "approvals: {} → {}",
+                approvers(&before.4),
+                approvers(&news.approvers())
+            ));
+        }
 
-            let msg = format!("<pre><code>{}</code></pre>\n", curl_command);
-            self.send_message(&msg, BotMsgType::AdminRoomHtmlNotice)
-                .await;
+        let written = news_store.write_news(event_id);
+
+        let msg = (!changes.is_empty()).then(|| {
+            format!(
+                "{}’s news entry [{}]: {}",
+                news.reporter_id,
+                link,
+                changes.join(", ")
+            )
+        });
+        store_warning(written, msg)
+    }
+
+    /// The entries of an image / video which are still valid, keyed like in `News`
+    async fn resync_file(
+        &self,
+        file_id: &EventId,
+        files: &HashMap<OwnedEventId, MediaFile>,
+    ) -> Result<Vec<(OwnedEventId, MediaFile)>, Error> {
+        let Some(file) = files.values().find(|f| f.event_id == file_id) else {
+            return Ok(Vec::new());
+        };
+
+        // The file got deleted
+        let event = utils::fetch_room_event(&self.reporting_room, file_id).await?;
+        let Some(file_event) = event.as_ref().and_then(utils::as_message_event) else {
+            return Ok(Vec::new());
+        };
+
+        // Attached without reaction (`!attach` or `attach_replies`)
+        let mut kept = Vec::new();
+        if files.contains_key(file_id) {
+            kept.push((file_id.to_owned(), file.clone()));
+        }
+
+        for (reaction_id, sender, emoji) in self.reactions(file_id).await? {
+            let allowed = sender == file_event.sender
+                || !self.config.restrict_notice
+                || self.can_react(&sender, &ReactionType::Notice).await;
+            if allowed && utils::emoji_cmp(&emoji, &self.config.notice_emoji) {
+                kept.push((reaction_id, file.clone()));
+            }
         }
+
+        Ok(kept)
     }
 
-    async fn restart_command(&self) {
-        self.send_message("Restarting hebbot…", BotMsgType::AdminRoomPlainNotice)
-            .await;
-        let _ = Command::new("/proc/self/exe").exec();
+    /// The reactions to a message as (reaction event id, sender, emoji),
+    /// without the reactions of the bot itself
+    async fn reactions(
+        &self,
+        event_id: &EventId,
+    ) -> Result<Vec<(OwnedEventId, OwnedUserId, String)>, Error> {
+        let events =
+            utils::room_event_relations(&self.reporting_room, event_id, RelationType::Annotation)
+                .await?;
+        let reactions = events
+            .into_iter()
+            .filter_map(|event| match event {
+                AnySyncTimelineEvent::MessageLike(AnySyncMessageLikeEvent::Reaction(
+                    SyncMessageLikeEvent::Original(reaction),
+                )) => Some(reaction),
+                _ => None,
+            })
+            .filter(|reaction| reaction.sender.as_str() != self.config.bot_user_id)
+            .map(|reaction| {
+                let emoji = reaction.content.relates_to.key;
+                let emoji = emoji.strip_suffix(" ?").map(str::to_owned).unwrap_or(emoji);
+                (reaction.event_id, reaction.sender, emoji)
+            })
+            .collect();
+        Ok(reactions)
     }
 
     async fn say_command(&self, msg: &str) {
@@ -880,10 +2070,23 @@ impl Bot {
             .await;
     }
 
+    async fn show_edition_command(&self, args: &str) {
+        let Some(edition) = self.edition_by_arg(args).await else {
+            return;
+        };
+
+        self.send_message(&edition.html_details(), BotMsgType::AdminRoomHtmlNotice)
+            .await;
+
+        for output in &edition.outputs {
+            self.upload_output(output).await;
+        }
+    }
+
     async fn status_command(&self) {
         let msg = {
             let news_store = self.news_store.lock().unwrap();
-            let news = news_store.news();
+            let news = news_store.numbered_news();
 
             let mut assigned_count = 0;
             let mut unassigned_count = 0;
@@ -891,24 +2094,37 @@ impl Bot {
             let mut assigned_list = String::new();
             let mut unassigned_list = String::new();
 
-            for n in &news {
+            for (i, n) in news.iter().enumerate() {
                 let link = self.message_link(&n.event_id);
-                let summary = n.message_summary();
+                let mut summary = n.message_summary();
+                if n.is_pinned() {
+                    summary.insert_str(0, "📌 ");
+                }
+                if self.config.review.enabled() && n.is_assigned() {
+                    let state = n.review_state(self.config.review.required_approvals);
+                    write!(summary, " ({})", state).unwrap();
+                }
 
                 if n.is_assigned() {
                     assigned_count += 1;
                     write!(
                         assigned_list,
-                        "- [{}] {}: {} <br>",
-                        link, n.reporter_id, summary
+                        "- #{} [{}] {}: {} <br>",
+                        i + 1,
+                        link,
+                        n.reporter_id,
+                        summary
                     )
                     .unwrap();
                 } else {
                     unassigned_count += 1;
                     write!(
                         unassigned_list,
-                        "- [{}] {}: {} <br>",
-                        link, n.reporter_id, summary
+                        "- #{} [{}] {}: {} <br>",
+                        i + 1,
+                        link,
+                        n.reporter_id,
+                        summary
                     )
                     .unwrap();
                 }
@@ -926,6 +2142,117 @@ impl Bot {
             .await;
     }
 
+    /// Removes the approvals of the editor from a news entry
+    async fn unapprove_command(&self, editor: &RoomMember, news_ref: &str) {
+        let msg = {
+            let news_store = self.news_store.lock().unwrap();
+            match news_store.news_by_ref(news_ref) {
+                Some(news) if news.remove_approvals_by(editor.user_id()) => {
+                    let written = news_store.write_news(&news.event_id);
+                    stored_message(
+                        written,
+                        format!(
+                            "✅ {} withdrew their approval of {}’s news entry [{}] ({}).",
+                            editor.user_id(),
+                            news.reporter_id,
+                            self.message_link(&news.event_id),
+                            news.review_state(self.config.review.required_approvals)
+                        ),
+                    )
+                }
+                Some(news) => format!(
+                    "❌ {}’s news entry [{}] isn’t approved by {}.",
+                    news.reporter_id,
+                    self.message_link(&news.event_id),
+                    editor.user_id()
+                ),
+                None if !news_ref.trim().is_empty() => {
+                    format!("❌ Unable to find the news entry {}.", news_ref.trim())
+                }
+                None => "❌ Usage: !unapprove <news>".to_string(),
+            }
+        };
+
+        self.send_message(&msg, BotMsgType::AdminRoomHtmlNotice)
+            .await;
+    }
+
+    /// Removes the given project / section assignment of a news entry, or all of them
+    async fn unassign_command(&self, editor: &RoomMember, args: &str) {
+        let (news_ref, term) = args.trim().split_once(' ').unwrap_or((args.trim(), ""));
+        let term = term.trim();
+        if !self.can_assign(editor, term).await {
+            return;
+        }
+
+        let msg = {
+            let news_store = self.news_store.lock().unwrap();
+            let news = news_store.news_by_ref(news_ref);
+            let msg = match (news, self.config.reaction_type_by_term(term)) {
+                (Some(news), _) if term.is_empty() => {
+                    let sections = news.remove_section_name(None);
+                    let projects = news.remove_project_name(None);
+                    self.unassign_message(editor, news, sections || projects, None)
+                }
+                (Some(news), ReactionType::Section(Some(section))) => {
+                    let removed = news.remove_section_name(Some(&section.name));
+                    self.unassign_message(editor, news, removed, Some(&section.title))
+                }
+                (Some(news), ReactionType::Project(Some(project))) => {
+                    let removed = news.remove_project_name(Some(&project.name));
+                    self.unassign_message(editor, news, removed, Some(&project.title))
+                }
+                (Some(_), _) => {
+                    format!("❌ Unable to find a project or section named {}.", term)
+                }
+                (None, _) if !news_ref.is_empty() => {
+                    format!("❌ Unable to find the news entry {}.", news_ref)
+                }
+                (None, _) => "❌ Usage: !unassign <news> [project|section]".to_string(),
+            };
+            match news {
+                Some(news) => stored_message(news_store.write_news(&news.event_id), msg),
+                None => msg,
+            }
+        };
+
+        self.send_message(&msg, BotMsgType::AdminRoomHtmlNotice)
+            .await;
+    }
+
+    fn unassign_message(
+        &self,
+        editor: &RoomMember,
+        news: &News,
+        removed: bool,
+        title: Option<&str>,
+    ) -> String {
+        let link = self.message_link(&news.event_id);
+        match (removed, title) {
+            (true, Some(title)) => format!(
+                "✅ {} removed “{}” from {}’s news entry [{}].",
+                editor.user_id(),
+                title,
+                news.reporter_id,
+                link
+            ),
+            (true, None) => format!(
+                "✅ {} removed all projects and sections from {}’s news entry [{}].",
+                editor.user_id(),
+                news.reporter_id,
+                link
+            ),
+            (false, Some(title)) => format!(
+                "❌ {}’s news entry [{}] isn’t assigned to “{}”.",
+                news.reporter_id, link, title
+            ),
+            (false, None) => format!(
+                "❌ {}’s news entry [{}] isn’t assigned to any project or section.",
+                news.reporter_id, link
+            ),
+        }
+    }
+
     async fn update_config_command(&self) {
         self.send_message(
             "Updating bot configuration…",
@@ -953,8 +2280,284 @@ impl Bot {
             .await;
     }
 
+    /// Looks up an archived edition by the number passed as command argument
+    async fn edition_by_arg(&self, args: &str) -> Option<Edition> {
+        let edition = args
+            .trim()
+            .trim_start_matches('#')
+            .parse::<u32>()
+            .ok()
+            .and_then(|number| self.news_store.lock().unwrap().edition(number).cloned());
+
+        if edition.is_none() {
+            let msg = format!("❌ Unable to find edition “{}”.", args);
+            self.send_message(&msg, BotMsgType::AdminRoomPlainNotice)
+                .await;
+        }
+
+        edition
+    }
+
+    /// Looks up the output by the name passed as command argument,
+    /// or returns all configured outputs if no name is passed
+    async fn outputs_by_arg(&self, args: &str) -> Option<Vec<Output>> {
+        let name = args.trim();
+        if name.is_empty() {
+            return Some(self.config.outputs());
+        }
+
+        let output = self.config.output_by_name(name);
+        if output.is_none() {
+            let msg = format!("❌ Unable to find output “{}”.", name);
+            self.send_message(&msg, BotMsgType::AdminRoomPlainNotice)
+                .await;
+        }
+
+        output.map(|output| vec![output])
+    }
+
+    /// Renders the given news for every output, and uploads the results into the admin room.
+    /// Returns the rendered outputs, or `None` if rendering failed.
+    async fn render_news(
+        &self,
+        news: Vec<News>,
+        outputs: &[Output],
+        editor: &str,
+        timestamp: DateTime<Utc>,
+    ) -> Option<Vec<RenderedOutput>> {
+        let mut results = Vec::new();
+        for output in outputs {
+            let result = {
+                let config = self.config.clone();
+                let mut templates = self.templates.lock().unwrap();
+
+                // Reloads the templates when they changed on disk
+                templates.environment().and_then(|env| {
+                    render::render(
+                        news.clone(),
+                        config,
+                        env,
+                        &output.template,
+                        editor,
+                        timestamp,
+                    )
+                    .map_err(Error::from)
+                })
+            };
+
+            match result {
+                Ok(result) => results.push((output, result)),
+                Err(error) => {
+                    let msg = format!(
+                        "❌ Could not render template for output “{}”: <pre>{}</pre>",
+                        output.name, error
+                    );
+                    self.send_message(&msg, BotMsgType::AdminRoomHtmlNotice)
+                        .await;
+                    return None;
+                }
+            }
+        }
+
+        let mut rendered_outputs = Vec::new();
+        for (output, result) in &results {
+            let rendered_output = RenderedOutput {
+                name: output.name.clone(),
+                filename: output.filename.clone(),
+                mime_type: output.mime_type.clone(),
+                content: result.rendered.clone(),
+            };
+            self.upload_output(&rendered_output).await;
+            rendered_outputs.push(rendered_output);
+        }
+
+        // The warnings, notes and files are the same for every output
+        let Some((_, result)) = results.first() else {
+            return Some(rendered_outputs);
+        };
+
+        // Send warnings
+        let warnings = utils::format_messages(true, &result.warnings);
+        if !result.warnings.is_empty() {
+            self.send_message(&warnings, BotMsgType::AdminRoomHtmlNotice)
+                .await;
+        }
+
+        // Send notes
+        let notes = utils::format_messages(false, &result.notes);
+        if !result.notes.is_empty() {
+            self.send_message(&notes, BotMsgType::AdminRoomHtmlNotice)
+                .await;
+        }
+
+        // Bundle all files (images/videos) with the rendered outputs
+        if !result.images.is_empty() || !result.videos.is_empty() {
+            self.bundle_media(&rendered_outputs, &result.images, &result.videos)
+                .await;
+        }
+
+        Some(rendered_outputs)
+    }
+
+    /// Downloads the media files, and hands them together with the rendered outputs
+    /// to the editor, as archive in the admin room or in a local directory
+    async fn bundle_media(
+        &self,
+        outputs: &[RenderedOutput],
+        images: &[MediaFile],
+        videos: &[MediaFile],
+    ) {
+        let mut bundle = Bundle::default();
+        for output in outputs {
+            bundle.add_file(&output.filename, output.content.clone().into_bytes());
+        }
+
+        let image_processing = &self.config.media.images;
+        let files = images
+            .iter()
+            .map(|file| (file, true))
+            .chain(videos.iter().map(|file| (file, false)));
+
+        let mut failed = Vec::new();
+        for (file, is_image) in files {
+            let filename = &file.unique_filename();
+            let request = MediaRequestParameters {
+                source: file.source(),
+                format: MediaFormat::File,
+            };
+
+            let content = match self.client.media().get_media_content(&request, true).await {
+                Ok(content) => content,
+                Err(err) => {
+                    warn!("Unable to download {}: {}", file.mxc_uri, err);
+                    failed.push(filename.clone());
+                    continue;
+                }
+            };
+
+            if !is_image || !image_processing.processes(file) {
+                bundle.add_file(filename, content);
+                continue;
+            }
+
+            // Processing images takes a while, don't block the other tasks
+            let processing = image_processing.clone();
+            let processed = tokio::task::spawn_blocking(move || processing.process(&content))
+                .await
+                .expect("Image processing task panicked");
+
+            match processed {
+                Ok(processed) => bundle.add_file(&image_processing.filename(file), processed),
+                Err(err) => {
+                    warn!("Unable to process {}: {}", filename, err);
+                    failed.push(filename.clone());
+                }
+            }
+        }
+
+        if !failed.is_empty() {
+            let msg = format!("⚠️ Could not download or process {}", failed.join(", "));
+            self.send_message(&msg, BotMsgType::AdminRoomHtmlNotice)
+                .await;
+        }
+
+        let media = &self.config.media;
+        let archive = match media.bundle {
+            BundleFormat::Zip => bundle.to_zip(),
+            BundleFormat::Tar => bundle.to_tar(),
+            BundleFormat::Directory => {
+                let msg = match bundle.write_to_directory(Path::new(&media.directory)) {
+                    Ok(()) => format!(
+                        "✅ Wrote {} files to <code>{}</code>",
+                        outputs.len() + images.len() + videos.len() - failed.len(),
+                        media.directory
+                    ),
+                    Err(err) => format!("❌ Could not write media bundle: {}", err),
+                };
+                self.send_message(&msg, BotMsgType::AdminRoomHtmlNotice)
+                    .await;
+                return;
+            }
+        };
+
+        match archive {
+            Ok(archive) => {
+                let (filename, mime_type) = media.bundle.archive().unwrap();
+                let mime_type = mime_type.parse::<mime::Mime>().unwrap();
+                self.send_file(filename, &mime_type, archive).await;
+            }
+            Err(err) => {
+                let msg = format!("❌ Could not create media bundle: {}", err);
+                self.send_message(&msg, BotMsgType::AdminRoomHtmlNotice)
+                    .await;
+            }
+        }
+    }
+
+    /// Uploads rendered content as file into the admin room
+    async fn upload_output(&self, output: &RenderedOutput) {
+        let mime_type = output
+            .mime_type
+            .parse::<mime::Mime>()
+            .unwrap_or(mime::TEXT_PLAIN_UTF_8);
+        let bytes = output.content.clone().into_bytes();
+        self.send_file(&output.filename, &mime_type, bytes).await;
+    }
+
+    /// Resolves the users mentioned in a message by pills, `m.mentions` or plain user ids,
+    /// using their current display names in the reporting room
+    async fn resolve_mentions(
+        &self,
+        message: &str,
+        html: Option<&str>,
+        mentions: Option<&Mentions>,
+    ) -> Vec<Mention> {
+        let pills = html.map(markdown::user_pills).unwrap_or_default();
+        let user_ids: Vec<OwnedUserId> = pills
+            .iter()
+            .map(|(user_id, _)| user_id.clone())
+            .chain(
+                mentions
+                    .into_iter()
+                    .flat_map(|m| m.user_ids.iter().cloned()),
+            )
+            .chain(utils::user_ids_in_text(message))
+            .collect();
+
+        let bot_id = self.client.user_id().unwrap();
+        let mut resolved: Vec<Mention> = Vec::new();
+        for user_id in user_ids {
+            if user_id == bot_id || resolved.iter().any(|m| m.user_id == user_id) {
+                continue;
+            }
+
+            let member = self
+                .reporting_room
+                .get_member(&user_id)
+                .await
+                .ok()
+                .flatten();
+            let display_name = match member {
+                Some(member) => member.name().to_owned(),
+                None => pills
+                    .iter()
+                    .find(|(id, text)| *id == user_id && !text.is_empty())
+                    .map(|(_, text)| text.clone())
+                    .unwrap_or_else(|| user_id.localpart().to_owned()),
+            };
+
+            resolved.push(Mention {
+                user_id,
+                display_name,
+            });
+        }
+
+        resolved
+    }
+
     async fn add_news(&self, news: News, notify_reporter: bool) {
         let link = self.message_link(&news.event_id);
+        let catching_up = self.catching_up.load(Ordering::Relaxed);
 
         // Check if the news already exists
         if self
@@ -964,6 +2567,11 @@ impl Bot {
             .news_by_message_id(&news.event_id)
             .is_some()
         {
+            // Already processed before the bot went offline
+            if catching_up {
+                return;
+            }
+
             let msg = format!(
                 "⚠️ Cannot resubmit a news item that has already been added. [{}]",
                 link
@@ -978,57 +2586,197 @@ impl Bot {
         let bot_display_name = self.client.account().get_display_name().await.ok().unwrap();
         news.set_message(utils::remove_bot_name(
             bot_id,
-            bot_display_name,
-            &news.message(),
+            bot_display_name.clone(),
+            &news.original_message(),
         ));
+        news.set_message_html(
+            news.original_message_html()
+                .map(|html| utils::remove_bot_pill(bot_id, bot_display_name, &html)),
+        );
 
         // Check min message length
         if news.message().len() > self.config.min_length {
-            if notify_reporter && !self.config.ack_text.is_empty() {
+            // Save it in message store
+            let stored = self.news_store.lock().unwrap().add_news(news.clone());
+            if let Err(err) = stored {
+                let msg = format!(
+                    "❌ Unable to store {}’s news entry [{}]: {}",
+                    news.reporter_id, link, err
+                );
+                self.send_message(&msg, BotMsgType::AdminRoomHtmlNotice)
+                    .await;
+                return;
+            }
+
+            if notify_reporter && !catching_up && !self.config.ack_text.is_empty() {
                 let msg = &self
                     .config
                     .ack_text
                     .replace("{{user}}", &news.reporter_display_name);
-                self.send_message(msg, BotMsgType::ReportingRoomPlainNotice)
-                    .await;
+                self.send_reporter_notice(msg, &news.event_id).await;
             }
 
             let msg = format!("✅ {} submitted a news entry. [{}]", news.reporter_id, link);
             self.send_message(&msg, BotMsgType::AdminRoomHtmlNotice)
                 .await;
 
-            // Pre-populate with emojis to facilitate the editor's work
-            for project in &self.config.projects {
-                let regex = Regex::new(&format!(
-                    "(?i)\\b{}\\b|\\b{}\\b",
-                    project.name, project.title,
-                ))
-                .unwrap();
-                if regex.is_match(&news.message()) {
-                    self.send_reaction(&format!("{} ?", &project.emoji), &news.event_id)
+            // Pre-populate with emojis to facilitate the editor's work,
+            // replayed news already got these before the bot went offline
+            if !catching_up {
+                for project in &self.config.projects {
+                    let regex = Regex::new(&format!(
+                        "(?i)\\b{}\\b|\\b{}\\b",
+                        project.name, project.title,
+                    ))
+                    .unwrap();
+                    if regex.is_match(&news.message()) {
+                        self.send_reaction(&format!("{} ?", &project.emoji), &news.event_id)
+                            .await;
+                    }
+                }
+                for section in self.config.sections_by_usual_reporter(&news.reporter_id) {
+                    self.send_reaction(&section.emoji, &EventId::parse(&news.event_id).unwrap())
                         .await;
                 }
             }
-            for section in self.config.sections_by_usual_reporter(&news.reporter_id) {
-                self.send_reaction(&section.emoji, &EventId::parse(&news.event_id).unwrap())
-                    .await;
-            }
-
-            // Save it in message store
-            self.news_store.lock().unwrap().add_news(news);
-        } else {
+        } else if !catching_up {
             let msg = format!(
                 "❌ {}: Your update is too short and was not stored. This limitation was set-up to limit spam.",
                 news.reporter_display_name
             );
-            self.send_message(&msg, BotMsgType::ReportingRoomPlainNotice)
-                .await;
+            self.send_reporter_notice(&msg, &news.event_id).await;
+        }
+    }
+
+    /// Editors have all permissions. Besides the configured editors,
+    /// these can be resolved from the current room membership / power levels.
+    async fn is_editor(&self, user_id: &UserId) -> bool {
+        if self.config.editors.iter().any(|id| id == user_id) {
+            return true;
+        }
+
+        // Only look up the memberships the sources need
+        let sources = &self.config.editor_sources;
+        let mut membership = SourceMembership::default();
+        if sources.admin_room_members {
+            membership.admin_room = utils::is_joined_member(&self.admin_room, user_id).await;
+        }
+
+        if sources.reporting_room_power_level.is_some() {
+            if let Ok(Some(member)) = self.reporting_room.get_member(user_id).await {
+                if *member.membership() == MembershipState::Join {
+                    membership.reporting_room_power_level = Some(member.power_level());
+                }
+            }
+        }
+
+        if let Some(space) = sources
+            .space_id
+            .as_ref()
+            .and_then(|space_id| self.client.get_room(space_id))
+        {
+            membership.space = utils::is_joined_member(&space, user_id).await;
+        }
+
+        sources.resolves_editor(&membership)
+    }
+
+    /// Editors can use all commands, other users depending on their roles
+    async fn can_use_command(&self, user_id: &UserId, command: &str) -> bool {
+        self.is_editor(user_id).await
+            || self
+                .config
+                .roles_of(user_id)
+                .any(|role| role.allows_command(command))
+    }
+
+    /// Whether the sender of an assignment command has the permission to (un)assign
+    /// the given project / section, without one the permission for all of them is required.
+    /// Sends an error message to the admin room otherwise.
+    async fn can_assign(&self, editor: &RoomMember, term: &str) -> bool {
+        let reaction_types = match self.config.reaction_type_by_term(term) {
+            _ if term.is_empty() => vec![ReactionType::Section(None), ReactionType::Project(None)],
+            ReactionType::None => return true,
+            reaction_type => vec![reaction_type],
+        };
+
+        for reaction_type in &reaction_types {
+            if !self.can_react(editor.user_id(), reaction_type).await {
+                let msg = format!(
+                    "❌ You don’t have the permission to assign news entries to “{}”.",
+                    if term.is_empty() {
+                        "all projects and sections"
+                    } else {
+                        term
+                    }
+                );
+                self.send_message(&msg, BotMsgType::AdminRoomPlainNotice)
+                    .await;
+                return false;
+            }
         }
+
+        true
+    }
+
+    /// Editors can use all reactions, other users depending on their roles.
+    /// Project maintainers can use the emoji of their project.
+    async fn can_react(&self, user_id: &UserId, reaction_type: &ReactionType) -> bool {
+        self.is_editor(user_id).await
+            || self
+                .config
+                .roles_of(user_id)
+                .any(|role| role.allows_reaction(reaction_type))
+            || matches!(reaction_type, ReactionType::Project(Some(project)) if project.is_maintained_by(user_id))
+    }
+
+    /// Whether the user maintains a project, without being an editor or having a role
+    async fn is_maintainer_only(&self, user_id: &UserId) -> bool {
+        self.config
+            .projects
+            .iter()
+            .any(|project| project.is_maintained_by(user_id))
+            && self.config.roles_of(user_id).next().is_none()
+            && !self.is_editor(user_id).await
+    }
+
+    /// Whether the reaction of the user gets applied to a news entry
+    async fn reaction_permission(
+        &self,
+        user_id: &UserId,
+        reaction_type: &ReactionType,
+    ) -> ReactionPermission {
+        let permitted = self.can_react(user_id, reaction_type).await;
+        let maintainer_only = self.is_maintainer_only(user_id).await;
+        reaction_type.permission(permitted, maintainer_only, self.config.restrict_notice)
     }
 
-    async fn is_editor(&self, member: &RoomMember) -> bool {
-        let user_id = member.user_id().to_owned();
-        self.config.editors.contains(&user_id)
+    /// Whether the event is a message in the admin room, e.g. an `!assign` command
+    async fn is_admin_room_event(&self, event_id: &EventId) -> Result<bool, Error> {
+        let event = utils::fetch_room_event(&self.admin_room, event_id).await?;
+        Ok(event.is_some())
+    }
+
+    fn approval_message(&self, editor: &RoomMember, news: &News) -> String {
+        format!(
+            "✅ {} approved {}’s news entry [{}] ({}).",
+            editor.user_id(),
+            news.reporter_id,
+            self.message_link(&news.event_id),
+            news.review_state(self.config.review.required_approvals)
+        )
+    }
+
+    /// Warning about a news entry which got changed by the reporter after `!edit`
+    fn edit_conflict_message(&self, news: &News) -> String {
+        format!(
+            "⚠️ {} changed their news entry [{}] after {} edited it. The edited text is still used, check the changes and update it with <code>!edit {} &lt;text&gt;</code>, or use the reporter’s message again with <code>!edit {}</code>.",
+            news.reporter_id,
+            self.message_link(&news.event_id),
+            news.editor_edit().map(|edit| edit.editor_id.to_string()).unwrap_or_default(),
+            news.event_id,
+            news.event_id
+        )
     }
 
     fn message_link(&self, event_id: &EventId) -> String {
@@ -1039,3 +2787,43 @@ impl Bot {
         )
     }
 }
+
+/// Prepends a warning to the message about a change, if the change couldn't be stored
+fn stored_message(result: Result<(), Error>, msg: String) -> String {
+    match result {
+        Ok(()) => msg,
+        Err(err) => format!("{}<br>{}", store_error_message(&err), msg),
+    }
+}
+
+/// Like `stored_message`, for changes which aren't always reported
+fn store_warning(result: Result<(), Error>, msg: Option<String>) -> Option<String> {
+    match (result, msg) {
+        (Ok(()), msg) => msg,
+        (Err(err), Some(msg)) => Some(stored_message(Err(err), msg)),
+        (Err(err), None) => Some(store_error_message(&err)),
+    }
+}
+
+fn store_error_message(err: &Error) -> String {
+    format!(
+        "❌ Unable to write the news store, the change gets lost when restarting: {}",
+        err
+    )
+}
+
+fn names_or_none(names: &[String]) -> String {
+    if names.is_empty() {
+        "none".to_string()
+    } else {
+        names.join(", ")
+    }
+}
+
+fn media_kind(event: &OriginalSyncRoomMessageEvent) -> &'static str {
+    if event.image().is_some() {
+        "image"
+    } else {
+        "video"
+    }
+}
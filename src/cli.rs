@@ -0,0 +1,150 @@
+use chrono::Utc;
+use regex::Regex;
+
+use std::fs;
+use std::path::PathBuf;
+
+use crate::{render, Config, NewsStore, Templates};
+
+const RENDER_USAGE: &str = "Usage: hebbot render [OPTIONS]
+
+Renders the stored news without connecting to Matrix.
+
+Options:
+    --config <PATH>     Configuration file (default: ./config.toml)
+    --output <NAME>     Configured output to render (default: first configured output)
+    --template <PATH>   Template file, overrides the template of the output
+    --template-dir <PATH>
+                        Directory used to resolve template names, includes and imports
+    --store <PATH>      News store, .db/.sqlite files are opened as SQLite database (default: ./store.json)
+    --out <PATH>        Output file (default: stdout)
+    --editor <NAME>     Editor name passed to the template (default: hebbot)";
+
+struct RenderArgs {
+    config: PathBuf,
+    output: Option<String>,
+    template: Option<PathBuf>,
+    template_dir: Option<PathBuf>,
+    store: PathBuf,
+    out: Option<PathBuf>,
+    editor: String,
+}
+
+impl RenderArgs {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut render_args = Self {
+            config: "./config.toml".into(),
+            output: None,
+            template: None,
+            template_dir: None,
+            store: "./store.json".into(),
+            out: None,
+            editor: "hebbot".into(),
+        };
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .cloned()
+                    .ok_or_else(|| format!("Missing value for {}", arg))
+            };
+
+            match arg.as_str() {
+                "--config" => render_args.config = value()?.into(),
+                "--output" => render_args.output = Some(value()?),
+                "--template" => render_args.template = Some(value()?.into()),
+                "--template-dir" => render_args.template_dir = Some(value()?.into()),
+                "--store" => render_args.store = value()?.into(),
+                "--out" => render_args.out = Some(value()?.into()),
+                "--editor" => render_args.editor = value()?,
+                _ => return Err(format!("Unknown argument “{}”", arg)),
+            }
+        }
+
+        Ok(render_args)
+    }
+}
+
+/// Runs a command line subcommand, returns the process exit code
+pub fn run(subcommand: &str, args: &[String]) -> i32 {
+    match subcommand {
+        "render" if args.iter().any(|a| a == "--help" || a == "-h") => {
+            println!("{}", RENDER_USAGE);
+            0
+        }
+        "render" => match RenderArgs::parse(args).and_then(render) {
+            Ok(()) => 0,
+            Err(err) => {
+                eprintln!("❌ {}", err);
+                1
+            }
+        },
+        _ => {
+            eprintln!("Unknown subcommand “{}”\n\n{}", subcommand, RENDER_USAGE);
+            2
+        }
+    }
+}
+
+fn render(args: RenderArgs) -> Result<(), String> {
+    let read = |path: &PathBuf| {
+        fs::read_to_string(path).map_err(|err| format!("Unable to read {:?}: {}", path, err))
+    };
+
+    let config_result = Config::parse(&read(&args.config)?)
+        .map_err(|err| format!("Unable to parse {:?}: {}", args.config, err))?;
+    print_messages("warning", &config_result.warnings);
+    print_messages("note", &config_result.notes);
+
+    let config = config_result.config;
+    let mut output = match &args.output {
+        Some(name) => config
+            .output_by_name(name)
+            .ok_or_else(|| format!("Unable to find output “{}”", name))?,
+        None => config.outputs().remove(0),
+    };
+    if let Some(template) = &args.template {
+        output.template = template.to_string_lossy().into_owned();
+    }
+
+    let mut templates = Templates::new(args.template_dir, vec![output.template.clone()]);
+    let env = templates
+        .environment()
+        .map_err(|err| format!("Unable to load template {:?}: {}", output.template, err))?;
+    if !args.store.exists() {
+        return Err(format!("News store {:?} doesn’t exist", args.store));
+    }
+    let news_store = NewsStore::open(&args.store)
+        .map_err(|err| format!("Unable to open news store {:?}: {}", args.store, err))?;
+
+    let result = render::render(
+        news_store.news(),
+        config,
+        env,
+        &output.template,
+        &args.editor,
+        Utc::now(),
+    )
+    .map_err(|err| format!("Could not render template: {:#}", err))?;
+
+    print_messages("warning", &result.warnings);
+    print_messages("note", &result.notes);
+
+    match args.out {
+        Some(path) => fs::write(&path, result.rendered)
+            .map_err(|err| format!("Unable to write {:?}: {}", path, err))?,
+        None => print!("{}", result.rendered),
+    }
+
+    Ok(())
+}
+
+fn print_messages(prefix: &str, messages: &[String]) {
+    // Messages are formatted for the admin room, replace links with their plain url
+    let link_regex = Regex::new("<a href=\"([^\"]*)\">[^<]*</a>").unwrap();
+
+    for message in messages {
+        eprintln!("{}: {}", prefix, link_regex.replace_all(message, "$1"));
+    }
+}
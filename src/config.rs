@@ -3,8 +3,12 @@ use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 use std::collections::HashSet;
+use std::env;
 
-use crate::{utils, Project, ReactionType, Section};
+use crate::{
+    output, utils, EditorSources, MediaConfig, Output, Project, ReactionType, ReviewConfig, Role,
+    Section, ADMIN_COMMANDS,
+};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Config {
@@ -16,10 +20,28 @@ pub struct Config {
     pub verbs: Vec<String>,
     pub min_length: usize,
     pub ack_text: String,
+    /// Each news entry lives in a thread: replies go into it,
+    /// and follow-up messages of the reporter get appended to the news entry
+    #[serde(default)]
+    pub use_threads: bool,
     pub update_config_command: String,
+    /// Users with all permissions
+    #[serde(default)]
     pub editors: Vec<OwnedUserId>,
+    /// Users who are editors because of their Matrix room membership / power level
+    #[serde(default)]
+    pub editor_sources: EditorSources,
+    /// Users with limited permissions
+    #[serde(default)]
+    pub roles: Vec<Role>,
     pub sections: Vec<Section>,
     pub projects: Vec<Project>,
+    #[serde(default)]
+    pub outputs: Vec<Output>,
+    #[serde(default)]
+    pub media: MediaConfig,
+    #[serde(default)]
+    pub review: ReviewConfig,
 }
 
 pub struct ConfigResult {
@@ -31,8 +53,12 @@ pub struct ConfigResult {
 impl Config {
     pub fn read() -> ConfigResult {
         let data = utils::file_from_env("CONFIG_PATH", "./config.toml");
-        let config: Config = toml::from_str(&data).expect("Unable to parse config file");
-        Self::validate_config(config)
+        Self::parse(&data).expect("Unable to parse config file")
+    }
+
+    pub fn parse(data: &str) -> Result<ConfigResult, toml::de::Error> {
+        let config: Config = toml::from_str(data)?;
+        Ok(Self::validate_config(config))
     }
 
     pub fn section_by_name(&self, name: &str) -> Option<Section> {
@@ -53,9 +79,38 @@ impl Config {
         None
     }
 
+    /// The configured outputs, or a single default output
+    /// using the template specified by the `TEMPLATE_PATH` env variable
+    pub fn outputs(&self) -> Vec<Output> {
+        if !self.outputs.is_empty() {
+            return self.outputs.clone();
+        }
+
+        vec![Output {
+            name: "default".into(),
+            template: env::var("TEMPLATE_PATH").unwrap_or("template.md".into()),
+            filename: "rendered.md".into(),
+            mime_type: output::default_mime_type(),
+        }]
+    }
+
+    pub fn output_by_name(&self, name: &str) -> Option<Output> {
+        self.outputs()
+            .into_iter()
+            .find(|output| output.name == name)
+    }
+
     pub fn reaction_type_by_emoji(&self, emoji: &str) -> ReactionType {
         if utils::emoji_cmp(&self.notice_emoji, emoji) {
             return ReactionType::Notice;
+        } else if self.review.enabled()
+            && self
+                .review
+                .emoji
+                .as_ref()
+                .is_some_and(|review_emoji| utils::emoji_cmp(review_emoji, emoji))
+        {
+            return ReactionType::Review;
         } else {
             // section
             for section in &self.sections {
@@ -74,6 +129,27 @@ impl Config {
         ReactionType::None
     }
 
+    /// Looks up a project or section by its name or emoji
+    pub fn reaction_type_by_term(&self, term: &str) -> ReactionType {
+        if let Some(project) = self.project_by_name(term) {
+            ReactionType::Project(Some(project))
+        } else if let Some(section) = self.section_by_name(term) {
+            ReactionType::Section(Some(section))
+        } else {
+            match self.reaction_type_by_emoji(term) {
+                ReactionType::Notice | ReactionType::Review => ReactionType::None,
+                reaction_type => reaction_type,
+            }
+        }
+    }
+
+    /// The roles the user is part of
+    pub fn roles_of<'a>(&'a self, user_id: &'a UserId) -> impl Iterator<Item = &'a Role> {
+        self.roles
+            .iter()
+            .filter(move |role| role.users.iter().any(|u| u == user_id))
+    }
+
     pub fn sections_by_usual_reporter(&self, reporter: &UserId) -> Vec<Section> {
         let mut sections_for_this_reporter = Vec::<Section>::new();
         for section in &self.sections {
@@ -103,13 +179,43 @@ impl Config {
             );
         }
 
-        if config.editors.is_empty() {
+        if config.editors.is_empty() && config.roles.is_empty() && !config.editor_sources.enabled()
+        {
             warnings.insert(
                 0,
                 "No editor is specified, the bot cannot be used without an editor".to_string(),
             );
         }
 
+        for role in &config.roles {
+            for command in &role.commands {
+                if command != "*" && !ADMIN_COMMANDS.contains(&command.as_str()) {
+                    warnings.insert(
+                        0,
+                        format!(
+                            "Role “{}” allows the unknown command “{}”, it needs to be an admin room command like “!status”.",
+                            role.name, command
+                        ),
+                    );
+                }
+            }
+            for reaction in &role.reactions {
+                let known = ["*", "notice", "review", "section", "project"]
+                    .contains(&reaction.as_str())
+                    || config.section_by_name(reaction).is_some()
+                    || config.project_by_name(reaction).is_some();
+                if !known {
+                    warnings.insert(
+                        0,
+                        format!(
+                            "Role “{}” allows the unknown reaction “{}”, it needs to be a reaction kind, section or project name.",
+                            role.name, reaction
+                        ),
+                    );
+                }
+            }
+        }
+
         if config.sections.is_empty() {
             notes.insert(
                 0,
@@ -188,6 +294,92 @@ impl Config {
             }
         }
 
+        let mut reviewers: HashSet<&OwnedUserId> = config.editors.iter().collect();
+        for role in &config.roles {
+            if role.allows_reaction(&ReactionType::Review) {
+                reviewers.extend(&role.users);
+            }
+        }
+        // Editors resolved from the room state are only known at runtime
+        if !config.editor_sources.enabled() && config.review.required_approvals > reviewers.len() {
+            warnings.insert(
+                0,
+                format!(
+                    "{} approvals are required to review news entries, but only {} editors and users with the review permission are configured.",
+                    config.review.required_approvals,
+                    reviewers.len()
+                ),
+            );
+        }
+
+        // The notice emoji takes precedence over the review emoji, which takes precedence over
+        // the section and project emojis
+        if let Some(review_emoji) = config
+            .review
+            .emoji
+            .as_deref()
+            .filter(|_| config.review.enabled())
+        {
+            let others = [("the notice emoji".to_string(), &config.notice_emoji)]
+                .into_iter()
+                .chain(
+                    config
+                        .sections
+                        .iter()
+                        .map(|section| (format!("section “{}”", section.name), &section.emoji)),
+                )
+                .chain(
+                    config
+                        .projects
+                        .iter()
+                        .map(|project| (format!("project “{}”", project.name), &project.emoji)),
+                );
+            for (other, emoji) in others {
+                if utils::emoji_cmp(emoji, review_emoji) {
+                    warnings.insert(
+                        0,
+                        format!(
+                            "The review emoji “{}” is also used by {}, this can lead to undefined behavior.",
+                            review_emoji, other
+                        ),
+                    );
+                }
+            }
+        }
+
+        let mut output_names = HashSet::new();
+        for output in &config.outputs {
+            if output.name.is_empty() || output.template.is_empty() || output.filename.is_empty() {
+                warnings.insert(
+                    0,
+                    format!(
+                        "Output “{}” requires a name, template and filename, this can lead to undefined behavior.",
+                        output.name
+                    ),
+                );
+            }
+
+            if output.mime_type.parse::<mime::Mime>().is_err() {
+                warnings.insert(
+                    0,
+                    format!(
+                        "Output “{}” has an invalid MIME type “{}”, the file will be uploaded as plain text.",
+                        output.name, output.mime_type
+                    ),
+                );
+            }
+
+            if !output_names.insert(output.name.clone()) {
+                warnings.insert(
+                    0,
+                    format!(
+                        "Output name “{}” is duplicated, this can lead to undefined behavior.",
+                        output.name
+                    ),
+                );
+            }
+        }
+
         // find duplicated emojis / names
         let mut emojis = HashSet::new();
         let mut emoji_duplicates = Vec::new();
@@ -0,0 +1,78 @@
+use chrono::{DateTime, Utc};
+use matrix_sdk::ruma::OwnedUserId;
+use serde::{Deserialize, Serialize};
+
+use std::fmt::Write;
+
+use crate::{News, RenderedOutput};
+
+/// A published issue, including the news it was rendered from
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Edition {
+    pub number: u32,
+    pub timestamp: DateTime<Utc>,
+    pub editor_id: OwnedUserId,
+    pub editor_display_name: String,
+    #[serde(default)]
+    pub outputs: Vec<RenderedOutput>,
+    /// Closed editions got cleared with `!clear`, and can't get republished anymore
+    pub closed: bool,
+    /// The news the outputs got rendered from, or the cleared news if it never got published
+    pub news: Vec<News>,
+    /// The news cleared with `!clear`, if they changed after publishing
+    #[serde(default)]
+    pub cleared_news: Vec<News>,
+}
+
+impl Edition {
+    pub fn html_summary(&self) -> String {
+        format!(
+            "#{} ({}) by {}: {} news entries{}",
+            self.number,
+            self.timestamp.format("%Y-%m-%d %H:%M"),
+            self.editor_display_name,
+            self.news.len(),
+            if self.closed { "" } else { " (open)" }
+        )
+    }
+
+    pub fn html_details(&self) -> String {
+        let mut news_list = String::new();
+        for news in &self.news {
+            write!(
+                news_list,
+                "- {}: {} <br>",
+                news.reporter_id,
+                news.message_summary()
+            )
+            .unwrap();
+        }
+
+        let outputs: Vec<&str> = self.outputs.iter().map(|o| o.name.as_str()).collect();
+        if !self.cleared_news.is_empty() {
+            write!(
+                news_list,
+                "<br>{} news entries got cleared after they changed since publishing <br>",
+                self.cleared_news.len()
+            )
+            .unwrap();
+        }
+
+        format!(
+            "<b>Edition Details</b><br>\
+            <b>Number</b>: {} <br>\
+            <b>Date</b>: {} <br>\
+            <b>Editor</b>: {} ({}) <br>\
+            <b>Closed</b>: {} <br>\
+            <b>Outputs</b>: {} <br>\
+            <b>News</b>: <br>{}",
+            self.number,
+            self.timestamp.format("%Y-%m-%d %H:%M"),
+            self.editor_display_name,
+            self.editor_id,
+            self.closed,
+            outputs.join(", "),
+            news_list
+        )
+    }
+}
@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use matrix_sdk::ruma::OwnedUserId;
+use serde::{Deserialize, Serialize};
+
+/// Text of a news entry set by an editor with `!edit`, used instead of the reporter's text
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EditorEdit {
+    pub editor_id: OwnedUserId,
+    pub timestamp: DateTime<Utc>,
+    pub message: String,
+    /// The reporter changed their message afterwards, the changes aren't part of this text
+    #[serde(default)]
+    pub conflict: bool,
+}
+
+impl EditorEdit {
+    pub fn new(editor_id: OwnedUserId, message: String) -> Self {
+        Self {
+            editor_id,
+            timestamp: Utc::now(),
+            message,
+            conflict: false,
+        }
+    }
+}
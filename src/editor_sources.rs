@@ -0,0 +1,97 @@
+use matrix_sdk::ruma::events::room::power_levels::UserPowerLevel;
+use matrix_sdk::ruma::{Int, OwnedRoomId};
+use serde::{Deserialize, Serialize};
+
+/// Users who are treated as editors in addition to the `editors` list,
+/// resolved from the current Matrix room state (`[editor_sources]` config section)
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct EditorSources {
+    /// Everyone who joined the admin room
+    #[serde(default)]
+    pub admin_room_members: bool,
+    /// Members of the reporting room with at least this power level
+    pub reporting_room_power_level: Option<i64>,
+    /// Everyone who joined this space, the bot needs to be a member of it
+    pub space_id: Option<OwnedRoomId>,
+}
+
+/// The memberships of a user in the rooms of the editor sources
+#[derive(Clone, Debug, Default)]
+pub struct SourceMembership {
+    pub admin_room: bool,
+    /// The power level in the reporting room, if the user joined it
+    pub reporting_room_power_level: Option<UserPowerLevel>,
+    pub space: bool,
+}
+
+impl EditorSources {
+    pub fn enabled(&self) -> bool {
+        self.admin_room_members
+            || self.reporting_room_power_level.is_some()
+            || self.space_id.is_some()
+    }
+
+    /// Whether a user with these memberships is an editor
+    pub fn resolves_editor(&self, membership: &SourceMembership) -> bool {
+        let power_level = self
+            .reporting_room_power_level
+            .zip(membership.reporting_room_power_level);
+
+        (self.admin_room_members && membership.admin_room)
+            || power_level.is_some_and(|(required, level)| level >= Int::new_saturating(required))
+            || (self.space_id.is_some() && membership.space)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use matrix_sdk::ruma::events::room::power_levels::UserPowerLevel;
+    use matrix_sdk::ruma::{owned_room_id, Int};
+
+    use super::{EditorSources, SourceMembership};
+
+    #[test]
+    fn editor_sources_resolution() {
+        let member = |admin_room, power_level: Option<i64>, space| SourceMembership {
+            admin_room,
+            reporting_room_power_level: power_level
+                .map(|level| UserPowerLevel::Int(Int::new_saturating(level))),
+            space,
+        };
+
+        // Nobody is an editor without sources
+        let sources = EditorSources::default();
+        assert!(!sources.enabled());
+        assert!(!sources.resolves_editor(&member(true, Some(100), true)));
+
+        let sources = EditorSources {
+            admin_room_members: true,
+            ..Default::default()
+        };
+        assert!(sources.resolves_editor(&member(true, None, false)));
+        assert!(!sources.resolves_editor(&member(false, Some(100), true)));
+
+        let sources = EditorSources {
+            reporting_room_power_level: Some(50),
+            ..Default::default()
+        };
+        assert!(sources.resolves_editor(&member(false, Some(50), false)));
+        assert!(sources.resolves_editor(&member(false, Some(100), false)));
+        assert!(!sources.resolves_editor(&member(false, Some(0), false)));
+        assert!(!sources.resolves_editor(&member(true, None, true)));
+
+        // Room creators have an infinite power level in room version 12
+        let creator = SourceMembership {
+            reporting_room_power_level: Some(UserPowerLevel::Infinite),
+            ..Default::default()
+        };
+        assert!(sources.resolves_editor(&creator));
+
+        let sources = EditorSources {
+            space_id: Some(owned_room_id!("!space:example.org")),
+            ..Default::default()
+        };
+        assert!(sources.resolves_editor(&member(false, None, true)));
+        assert!(!sources.resolves_editor(&member(true, Some(100), false)));
+    }
+}
@@ -1,5 +1,98 @@
+use matrix_sdk::ruma::{IdParseError, OwnedEventId};
+
+use std::fmt;
+
 #[derive(Debug)]
 pub enum Error {
     NewsEventIdNotFound,
     RedactionEventIdNotFound,
+    UnknownStoreBackend(String),
+    UnsupportedStoreVersion(usize),
+    OutdatedStoreVersion(usize),
+    InvalidId(IdParseError),
+    Matrix(matrix_sdk::Error),
+    UnableToDecrypt(OwnedEventId),
+    Image(image::ImageError),
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Sqlite(rusqlite::Error),
+    Template(minijinja::Error),
+    Zip(zip::result::ZipError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NewsEventIdNotFound => write!(f, "news event id not found"),
+            Error::RedactionEventIdNotFound => write!(f, "redaction event id not found"),
+            Error::UnknownStoreBackend(name) => write!(f, "unknown store backend “{}”", name),
+            Error::UnsupportedStoreVersion(version) => write!(
+                f,
+                "store schema version {} is newer than supported by this version of hebbot",
+                version
+            ),
+            Error::OutdatedStoreVersion(version) => write!(
+                f,
+                "store schema version {} is outdated, start the bot once to migrate it",
+                version
+            ),
+            Error::InvalidId(err) => write!(f, "invalid matrix id: {}", err),
+            Error::Matrix(err) => write!(f, "matrix error: {}", err),
+            Error::UnableToDecrypt(event_id) => write!(f, "unable to decrypt event {}", event_id),
+            Error::Image(err) => write!(f, "image error: {}", err),
+            Error::Io(err) => write!(f, "i/o error: {}", err),
+            Error::Json(err) => write!(f, "json error: {}", err),
+            Error::Sqlite(err) => write!(f, "sqlite error: {}", err),
+            Error::Template(err) => write!(f, "template error: {:#}", err),
+            Error::Zip(err) => write!(f, "zip error: {}", err),
+        }
+    }
+}
+
+impl From<IdParseError> for Error {
+    fn from(err: IdParseError) -> Self {
+        Error::InvalidId(err)
+    }
+}
+
+impl From<matrix_sdk::Error> for Error {
+    fn from(err: matrix_sdk::Error) -> Self {
+        Error::Matrix(err)
+    }
+}
+
+impl From<image::ImageError> for Error {
+    fn from(err: image::ImageError) -> Self {
+        Error::Image(err)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Self {
+        Error::Sqlite(err)
+    }
+}
+
+impl From<minijinja::Error> for Error {
+    fn from(err: minijinja::Error) -> Self {
+        Error::Template(err)
+    }
+}
+
+impl From<zip::result::ZipError> for Error {
+    fn from(err: zip::result::ZipError) -> Self {
+        Error::Zip(err)
+    }
 }
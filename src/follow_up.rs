@@ -0,0 +1,27 @@
+use matrix_sdk::ruma::OwnedEventId;
+use serde::{Deserialize, Serialize};
+
+use crate::markdown;
+
+/// A message the reporter posted in the thread of their news entry
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FollowUp {
+    pub event_id: OwnedEventId,
+    pub message: String,
+    pub message_html: Option<String>,
+    /// Markdown conversion of `message_html`
+    pub message_markdown: Option<String>,
+}
+
+impl FollowUp {
+    pub fn new(event_id: OwnedEventId, message: String, message_html: Option<String>) -> Self {
+        let message_markdown = message_html.as_deref().map(markdown::html_to_markdown);
+
+        Self {
+            event_id,
+            message,
+            message_html,
+            message_markdown,
+        }
+    }
+}
@@ -3,29 +3,61 @@ extern crate log;
 
 mod bot;
 mod bot_message_type;
+mod cli;
 mod config;
+mod edition;
+mod editor_edit;
+mod editor_sources;
 mod error;
+mod follow_up;
+mod markdown;
+mod media;
+mod media_file;
+mod mention;
 mod news;
 mod news_store;
+mod output;
 mod project;
 mod reaction_type;
 mod render;
+mod review;
+mod role;
 mod section;
+mod session;
+mod templates;
 mod utils;
 
 pub use bot_message_type::BotMessageType;
 pub use config::Config;
+pub use edition::Edition;
+pub use editor_edit::EditorEdit;
+pub use editor_sources::{EditorSources, SourceMembership};
 pub use error::Error;
+pub use follow_up::FollowUp;
+pub use media::{Bundle, BundleFormat, ImageProcessing, MediaConfig};
+pub use media_file::MediaFile;
+pub use mention::Mention;
 pub use news::News;
-pub use news_store::NewsStore;
+pub use news_store::{NewsStore, RelatedNews};
+pub use output::{Output, RenderedOutput};
 pub use project::Project;
-pub use reaction_type::ReactionType;
+pub use reaction_type::{ReactionPermission, ReactionType};
+pub use review::{ReviewConfig, ReviewState};
+pub use role::{Role, ADMIN_COMMANDS};
 pub use section::Section;
+pub use session::Session;
+pub use templates::Templates;
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() {
     pretty_env_logger::init_timed();
 
+    // Subcommands which don't require a Matrix session
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(subcommand) = args.get(1) {
+        std::process::exit(cli::run(subcommand, &args[2..]));
+    }
+
     info!("Starting hebbot");
     bot::Bot::run().await;
 }
@@ -0,0 +1,486 @@
+use matrix_sdk::ruma::matrix_uri::MatrixId;
+use matrix_sdk::ruma::{MatrixToUri, OwnedUserId};
+use regex::Regex;
+use ruma_html::{Html, NodeData, NodeRef};
+
+use std::sync::LazyLock;
+
+/// Separates block level elements, gets resolved to blank lines by `resolve_blocks`
+const BLOCK: char = '\u{1e}';
+
+static URL_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"https?://\S+").unwrap());
+
+/// Converts the HTML `formatted_body` of a Matrix message to Markdown
+pub fn html_to_markdown(html: &str) -> String {
+    let html = Html::parse(html);
+    resolve_blocks(&convert_nodes(html.children()))
+}
+
+/// Mention pills (user id, pill text) contained in the HTML, reply fallbacks are skipped
+pub fn user_pills(html: &str) -> Vec<(OwnedUserId, String)> {
+    let html = Html::parse(html);
+    let mut pills = Vec::new();
+    for node in html.children() {
+        collect_user_pills(&node, &mut pills);
+    }
+    pills
+}
+
+fn collect_user_pills(node: &NodeRef, pills: &mut Vec<(OwnedUserId, String)>) {
+    match element_name(node) {
+        Some("mx-reply") => (),
+        Some("a") => {
+            let uri = attribute(node, "href").and_then(|href| MatrixToUri::parse(&href).ok());
+            if let Some(MatrixId::User(user_id)) = uri.map(|uri| uri.id().clone()) {
+                pills.push((user_id, text_content(node).trim().to_string()));
+            }
+        }
+        _ => {
+            for child in node.children() {
+                collect_user_pills(&child, pills);
+            }
+        }
+    }
+}
+
+fn convert_nodes(nodes: impl Iterator<Item = NodeRef>) -> String {
+    nodes.map(|node| convert_node(&node)).collect()
+}
+
+fn convert_node(node: &NodeRef) -> String {
+    match node.data() {
+        NodeData::Text(text) => {
+            let text = text.borrow().replace(BLOCK, "");
+            let mut text = collapse_whitespace(&text);
+
+            // Line breaks are usually followed by a newline in the source
+            if node
+                .prev_sibling()
+                .is_some_and(|n| element_name(&n) == Some("br"))
+            {
+                text = text.trim_start().to_string();
+            }
+
+            escape(&text)
+        }
+        NodeData::Element(element) => convert_element(node, &element.name.local),
+        _ => String::new(),
+    }
+}
+
+fn convert_element(node: &NodeRef, name: &str) -> String {
+    let children = || convert_nodes(node.children());
+
+    match name {
+        "p" | "div" | "details" | "summary" => block(&children()),
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            let level = name[1..].parse().unwrap_or(1);
+            let title = resolve_blocks(&children()).replace("  \n", " ");
+            block(&format!("{} {}", "#".repeat(level), title))
+        }
+        "blockquote" => {
+            let quote = resolve_blocks(&children())
+                .lines()
+                .map(|line| {
+                    if line.is_empty() {
+                        ">".to_string()
+                    } else {
+                        format!("> {}", line)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            block(&quote)
+        }
+        "ul" | "ol" => {
+            let mut number: u64 = attribute(node, "start")
+                .and_then(|start| start.parse().ok())
+                .unwrap_or(1);
+
+            let mut items = Vec::new();
+            for item in node.children() {
+                if element_name(&item) != Some("li") {
+                    continue;
+                }
+
+                let marker = if name == "ol" {
+                    number += 1;
+                    format!("{}. ", number - 1)
+                } else {
+                    "- ".to_string()
+                };
+                items.push(list_item(&item, &marker));
+            }
+            block(&items.join("\n"))
+        }
+        "li" => block(&list_item(node, "- ")),
+        "pre" => {
+            let code = text_content(node);
+            let code = code.strip_suffix('\n').unwrap_or(&code);
+            let language = node
+                .first_child()
+                .filter(|n| element_name(n) == Some("code"))
+                .and_then(|n| attribute(&n, "class"))
+                .and_then(|class| {
+                    class
+                        .split_whitespace()
+                        .find_map(|c| c.strip_prefix("language-").map(str::to_string))
+                })
+                .unwrap_or_default();
+
+            let fence = "`".repeat(longest_backtick_run(code).max(2) + 1);
+            block(&format!("{fence}{language}\n{code}\n{fence}"))
+        }
+        "code" => {
+            let code = text_content(node);
+            let fence = "`".repeat(longest_backtick_run(&code) + 1);
+            if code.starts_with('`') || code.ends_with('`') {
+                format!("{fence} {code} {fence}")
+            } else {
+                format!("{fence}{code}{fence}")
+            }
+        }
+        "b" | "strong" => wrap(&children(), "**"),
+        "i" | "em" => wrap(&children(), "*"),
+        "del" | "s" | "strike" => wrap(&children(), "~~"),
+        "a" => {
+            let text = children();
+            let Some(href) = attribute(node, "href") else {
+                return text;
+            };
+
+            // Mention pills without text get the user id as label
+            let text = match MatrixToUri::parse(&href).ok().map(|uri| uri.id().clone()) {
+                Some(MatrixId::User(user_id)) if text.trim().is_empty() => escape(user_id.as_str()),
+                _ => text,
+            };
+
+            if text.is_empty() || text == href {
+                format!("<{}>", href)
+            } else {
+                format!("[{}]({})", text, link_destination(&href))
+            }
+        }
+        "img" => {
+            let alt = attribute(node, "alt").unwrap_or_default();
+            match attribute(node, "src") {
+                Some(src) => format!("![{}]({})", escape(&alt), link_destination(&src)),
+                None => escape(&alt),
+            }
+        }
+        "table" => block(&table(node)),
+        "br" => "  \n".to_string(),
+        "hr" => block("---"),
+        // Reply fallbacks are duplicating the replied message
+        "mx-reply" => String::new(),
+        _ => children(),
+    }
+}
+
+/// Converts a list item, continuation lines are indented to align with the marker
+fn list_item(item: &NodeRef, marker: &str) -> String {
+    let mut content = resolve_blocks(&convert_nodes(item.children()));
+
+    // Keep lists tight, unless the item contains explicit paragraphs
+    if !item.children().any(|n| element_name(&n) == Some("p")) {
+        content = content.replace("\n\n", "\n");
+    }
+
+    let indent = " ".repeat(marker.chars().count());
+    let mut lines = content.lines();
+    let mut item = format!("{}{}", marker, lines.next().unwrap_or_default());
+    for line in lines {
+        item.push('\n');
+        if !line.is_empty() {
+            item.push_str(&indent);
+            item.push_str(line);
+        }
+    }
+
+    item
+}
+
+fn table(node: &NodeRef) -> String {
+    let mut rows = Vec::new();
+    collect_table_rows(node, &mut rows);
+
+    let mut lines = Vec::new();
+    for (index, row) in rows.iter().enumerate() {
+        lines.push(format!("| {} |", row.join(" | ")));
+        if index == 0 {
+            lines.push(format!("|{}", " --- |".repeat(row.len())));
+        }
+    }
+
+    lines.join("\n")
+}
+
+fn collect_table_rows(node: &NodeRef, rows: &mut Vec<Vec<String>>) {
+    for child in node.children() {
+        match element_name(&child) {
+            Some("thead" | "tbody" | "tfoot") => collect_table_rows(&child, rows),
+            Some("tr") => {
+                let cells = child
+                    .children()
+                    .filter(|n| matches!(element_name(n), Some("th" | "td")))
+                    .map(|cell| {
+                        resolve_blocks(&convert_nodes(cell.children()))
+                            .replace("  \n", " ")
+                            .replace('\n', " ")
+                            .replace('|', "\\|")
+                    })
+                    .collect();
+                rows.push(cells);
+            }
+            _ => (),
+        }
+    }
+}
+
+fn block(content: &str) -> String {
+    format!("{BLOCK}{content}{BLOCK}")
+}
+
+/// Joins the block level elements with blank lines
+fn resolve_blocks(content: &str) -> String {
+    content
+        .split(BLOCK)
+        .map(|part| part.trim_matches(|c: char| c.is_whitespace() && c != '\u{a0}'))
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Wraps inline content with the given delimiter, surrounding whitespace stays outside
+fn wrap(content: &str, delimiter: &str) -> String {
+    let trimmed = content.trim();
+    if trimmed.is_empty() {
+        return content.to_string();
+    }
+
+    let start = content.len() - content.trim_start().len();
+    let end = content.trim_end().len();
+    format!(
+        "{}{delimiter}{trimmed}{delimiter}{}",
+        &content[..start],
+        &content[end..]
+    )
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut last_was_whitespace = false;
+
+    for c in text.chars() {
+        if c.is_ascii_whitespace() {
+            if !last_was_whitespace {
+                collapsed.push(' ');
+            }
+            last_was_whitespace = true;
+        } else {
+            collapsed.push(c);
+            last_was_whitespace = false;
+        }
+    }
+
+    collapsed
+}
+
+/// Escapes Markdown syntax characters, URLs are kept as they are.
+/// The text might start a line, so block markers at its start get escaped as well.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut last = 0;
+
+    for url in URL_REGEX.find_iter(text) {
+        escape_into(&mut escaped, &text[last..url.start()], last == 0);
+        escaped.push_str(url.as_str());
+        last = url.end();
+    }
+    escape_into(&mut escaped, &text[last..], last == 0);
+
+    escaped
+}
+
+fn escape_into(escaped: &mut String, text: &str, line_start: bool) {
+    let text = if line_start {
+        escape_block_marker(escaped, text)
+    } else {
+        text
+    };
+
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+}
+
+/// Escapes a heading or list marker (or a setext underline / thematic break),
+/// returns the text following the escaped part. Quote markers are escaped like any other `>`.
+fn escape_block_marker<'a>(escaped: &mut String, text: &'a str) -> &'a str {
+    let line = text.trim_start();
+    escaped.push_str(&text[..text.len() - line.len()]);
+
+    let ends_marker = |rest: &str| rest.chars().next().is_none_or(char::is_whitespace);
+    let Some(first) = line.chars().next() else {
+        return line;
+    };
+
+    match first {
+        '#' if ends_marker(line.trim_start_matches('#')) => escaped.push('\\'),
+        '-' | '+' | '=' if ends_marker(&line[1..]) || line[1..].starts_with(first) => {
+            escaped.push('\\')
+        }
+        '0'..='9' => {
+            let rest = line.trim_start_matches(|c: char| c.is_ascii_digit());
+            if rest.starts_with(['.', ')']) && ends_marker(&rest[1..]) {
+                let number = &line[..line.len() - rest.len()];
+                escaped.push_str(number);
+                escaped.push('\\');
+                return rest;
+            }
+        }
+        _ => {}
+    }
+    line
+}
+
+fn link_destination(url: &str) -> String {
+    if url.contains([' ', '(', ')']) {
+        format!("<{}>", url)
+    } else {
+        url.to_string()
+    }
+}
+
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default()
+}
+
+fn text_content(node: &NodeRef) -> String {
+    match node.data() {
+        NodeData::Text(text) => text.borrow().to_string(),
+        NodeData::Element(_) if element_name(node) == Some("br") => "\n".to_string(),
+        _ => node.children().map(|n| text_content(&n)).collect(),
+    }
+}
+
+fn element_name(node: &NodeRef) -> Option<&str> {
+    node.as_element().map(|element| &*element.name.local)
+}
+
+fn attribute(node: &NodeRef, name: &str) -> Option<String> {
+    let element = node.as_element()?;
+    let attrs = element.attrs.borrow();
+    attrs
+        .iter()
+        .find(|attr| &*attr.name.local == name)
+        .map(|attr| attr.value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use matrix_sdk::ruma::owned_user_id;
+
+    use super::{html_to_markdown, user_pills};
+
+    #[test]
+    fn html_to_markdown_inline() {
+        assert_eq!(
+            html_to_markdown("Some <b>bold</b>, <em>italic </em>and <del>struck</del> text"),
+            "Some **bold**, *italic* and ~~struck~~ text"
+        );
+        assert_eq!(
+            html_to_markdown("Use <code>cargo build</code> or <code>a`b</code>"),
+            "Use `cargo build` or ``a`b``"
+        );
+        assert_eq!(
+            html_to_markdown("Escape *this* and_that, keep https://example.org/a_b"),
+            "Escape \\*this\\* and\\_that, keep https://example.org/a_b"
+        );
+        assert_eq!(
+            html_to_markdown("First line<br>\nSecond line"),
+            "First line  \nSecond line"
+        );
+    }
+
+    #[test]
+    fn html_to_markdown_escape_blocks() {
+        assert_eq!(
+            html_to_markdown("<p>&lt;b&gt;not bold&lt;/b&gt; -&gt; #42</p>"),
+            "\\<b\\>not bold\\</b\\> -\\> #42"
+        );
+        assert_eq!(
+            html_to_markdown("# Not a heading<br>#hashtag"),
+            "\\# Not a heading  \n#hashtag"
+        );
+        assert_eq!(
+            html_to_markdown("<p>- not a list</p><p>+ nor this</p><p>> no quote</p>"),
+            "\\- not a list\n\n\\+ nor this\n\n\\> no quote"
+        );
+        assert_eq!(
+            html_to_markdown("Released<br>1. May<br>2) June<br>---<br>2024 -1"),
+            "Released  \n1\\. May  \n2\\) June  \n\\---  \n2024 -1"
+        );
+    }
+
+    #[test]
+    fn html_to_markdown_links() {
+        assert_eq!(
+            html_to_markdown(
+                "<a href=\"https://matrix.to/#/@alice:matrix.local\">Alice</a>: \
+                see <a href=\"https://example.org\">the website</a>"
+            ),
+            "[Alice](https://matrix.to/#/@alice:matrix.local): see [the website](https://example.org)"
+        );
+        assert_eq!(
+            html_to_markdown("<a href=\"https://matrix.to/#/@alice:matrix.local\"></a>"),
+            "[@alice:matrix.local](https://matrix.to/#/@alice:matrix.local)"
+        );
+        assert_eq!(
+            html_to_markdown("<a href=\"https://example.org\">https://example.org</a>"),
+            "<https://example.org>"
+        );
+    }
+
+    #[test]
+    fn html_to_markdown_blocks() {
+        let html = "<mx-reply><blockquote>Replied message</blockquote></mx-reply>\
+            <h3>Release</h3>\
+            <p>Changes:</p>\n\
+            <ul>\n<li>First</li>\n<li>Second\n<ol start=\"3\"><li>Nested</li></ol></li>\n</ul>\n\
+            <blockquote><p>Quote</p><p>continued</p></blockquote>\
+            <pre><code class=\"language-rust\">fn main() {\n    println!(\"```\");\n}\n</code></pre>";
+
+        assert_eq!(
+            html_to_markdown(html),
+            "### Release\n\n\
+            Changes:\n\n\
+            - First\n\
+            - Second\n  3. Nested\n\n\
+            > Quote\n>\n> continued\n\n\
+            ````rust\nfn main() {\n    println!(\"```\");\n}\n````"
+        );
+    }
+
+    #[test]
+    fn user_pills_in_html() {
+        let html = "<mx-reply><a href=\"https://matrix.to/#/@bob:matrix.local\">Bob</a></mx-reply>\
+            Thanks to <a href=\"https://matrix.to/#/@alice:matrix.local\">Alice</a> \
+            and <a href=\"https://matrix.to/#/%40carol%3Amatrix.local\"> Carol </a> in \
+            <a href=\"https://matrix.to/#/#room:matrix.local\">#room</a>";
+
+        assert_eq!(
+            user_pills(html),
+            vec![
+                (owned_user_id!("@alice:matrix.local"), "Alice".to_owned()),
+                (owned_user_id!("@carol:matrix.local"), "Carol".to_owned()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,397 @@
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageDecoder, ImageReader};
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::Path;
+
+use crate::{Error, MediaFile};
+
+/// Media handling settings (`[media]` config section)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MediaConfig {
+    #[serde(default)]
+    pub bundle: BundleFormat,
+    /// Target directory of the `directory` bundle format
+    #[serde(default = "default_bundle_directory")]
+    pub directory: String,
+    #[serde(default)]
+    pub images: ImageProcessing,
+    /// Images / videos only get associated with news entries posted within this many hours
+    #[serde(default = "default_association_window_hours")]
+    pub association_window_hours: u32,
+    /// Attach images / videos posted as reply or in the thread of a news entry
+    /// without waiting for the notice emoji
+    #[serde(default)]
+    pub attach_replies: bool,
+}
+
+impl Default for MediaConfig {
+    fn default() -> Self {
+        Self {
+            bundle: BundleFormat::default(),
+            directory: default_bundle_directory(),
+            images: ImageProcessing::default(),
+            association_window_hours: default_association_window_hours(),
+            attach_replies: false,
+        }
+    }
+}
+
+impl MediaConfig {
+    pub fn association_window(&self) -> chrono::Duration {
+        chrono::Duration::hours(self.association_window_hours.into())
+    }
+}
+
+fn default_bundle_directory() -> String {
+    "./bundle".into()
+}
+
+fn default_association_window_hours() -> u32 {
+    24
+}
+
+/// How the rendered outputs and their media files are handed to the editor
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BundleFormat {
+    /// Upload a zip archive into the admin room
+    #[default]
+    Zip,
+    /// Upload a tar archive into the admin room
+    Tar,
+    /// Write the files into a local directory
+    Directory,
+}
+
+impl BundleFormat {
+    /// Filename and MIME type of the uploaded archive
+    pub fn archive(&self) -> Option<(&'static str, &'static str)> {
+        match self {
+            BundleFormat::Zip => Some(("bundle.zip", "application/zip")),
+            BundleFormat::Tar => Some(("bundle.tar", "application/x-tar")),
+            BundleFormat::Directory => None,
+        }
+    }
+}
+
+/// Processing applied to images before they get bundled (`[media.images]` config section)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ImageProcessing {
+    /// Wider images get scaled down to this width
+    pub max_width: Option<u32>,
+    #[serde(default)]
+    pub format: ImageFormat,
+    /// JPEG quality (1-100)
+    #[serde(default = "default_quality")]
+    pub quality: u8,
+    /// Re-encode images to remove EXIF and other metadata
+    #[serde(default)]
+    pub strip_metadata: bool,
+}
+
+impl Default for ImageProcessing {
+    fn default() -> Self {
+        Self {
+            max_width: None,
+            format: ImageFormat::default(),
+            quality: default_quality(),
+            strip_metadata: false,
+        }
+    }
+}
+
+fn default_quality() -> u8 {
+    85
+}
+
+/// Target format of processed images
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    /// Keep the format of the uploaded image
+    #[default]
+    Original,
+    Jpeg,
+    Png,
+    /// Lossless WebP, `quality` doesn't apply
+    Webp,
+}
+
+impl ImageFormat {
+    /// MIME type of the converted images, `None` if the original format is kept
+    pub fn mime_type(&self) -> Option<&'static str> {
+        match self {
+            ImageFormat::Original => None,
+            ImageFormat::Jpeg => Some("image/jpeg"),
+            ImageFormat::Png => Some("image/png"),
+            ImageFormat::Webp => Some("image/webp"),
+        }
+    }
+}
+
+impl ImageProcessing {
+    /// Whether the given image file gets processed.
+    /// Formats which could lose information (e.g. animated GIFs or SVGs) are kept as they are.
+    pub fn processes(&self, file: &MediaFile) -> bool {
+        let enabled =
+            self.max_width.is_some() || self.format != ImageFormat::Original || self.strip_metadata;
+        let supported =
+            source_format(file).is_some_and(|format| SUPPORTED_FORMATS.contains(&format));
+
+        enabled && supported
+    }
+
+    /// The unique filename of the processed image
+    pub fn filename(&self, file: &MediaFile) -> String {
+        let filename = file.unique_filename();
+        let extension = match self.format {
+            ImageFormat::Original => return filename,
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::Png => "png",
+            ImageFormat::Webp => "webp",
+        };
+
+        if self.processes(file) {
+            Path::new(&filename)
+                .with_extension(extension)
+                .to_string_lossy()
+                .into_owned()
+        } else {
+            filename
+        }
+    }
+
+    /// Rotates the image according to its EXIF orientation, resizes and re-encodes it.
+    /// Metadata doesn't get copied to the new image.
+    pub fn process(&self, content: &[u8]) -> Result<Vec<u8>, Error> {
+        let original_format = image::guess_format(content)?;
+        let reader = ImageReader::with_format(Cursor::new(content), original_format);
+        let mut decoder = reader.into_decoder()?;
+        let orientation = decoder.orientation()?;
+        let mut image = DynamicImage::from_decoder(decoder)?;
+        image.apply_orientation(orientation);
+
+        if let Some(max_width) = self.max_width {
+            if image.width() > max_width {
+                image = image.resize(max_width, u32::MAX, FilterType::Lanczos3);
+            }
+        }
+
+        let format = match self.format {
+            ImageFormat::Original => original_format,
+            ImageFormat::Jpeg => image::ImageFormat::Jpeg,
+            ImageFormat::Png => image::ImageFormat::Png,
+            ImageFormat::Webp => image::ImageFormat::WebP,
+        };
+
+        let mut processed = Cursor::new(Vec::new());
+        if format == image::ImageFormat::Jpeg {
+            // JPEG doesn't support transparency
+            let encoder = JpegEncoder::new_with_quality(&mut processed, self.quality.clamp(1, 100));
+            image.to_rgb8().write_with_encoder(encoder)?;
+        } else {
+            image.write_to(&mut processed, format)?;
+        }
+
+        Ok(processed.into_inner())
+    }
+}
+
+/// The format of an image by its MIME type, or its filename extension if the MIME type is unknown
+fn source_format(file: &MediaFile) -> Option<image::ImageFormat> {
+    file.mimetype
+        .as_deref()
+        .and_then(image::ImageFormat::from_mime_type)
+        .or_else(|| {
+            Path::new(&file.filename)
+                .extension()
+                .and_then(|e| e.to_str())
+                .and_then(image::ImageFormat::from_extension)
+        })
+}
+
+const SUPPORTED_FORMATS: &[image::ImageFormat] = &[
+    image::ImageFormat::Bmp,
+    image::ImageFormat::Jpeg,
+    image::ImageFormat::Png,
+    image::ImageFormat::Tiff,
+    image::ImageFormat::WebP,
+];
+
+/// The files of a rendered post, the outputs next to the media files they reference
+#[derive(Default, Debug)]
+pub struct Bundle {
+    files: Vec<(String, Vec<u8>)>,
+}
+
+impl Bundle {
+    pub fn add_file(&mut self, filename: &str, content: Vec<u8>) {
+        self.files.push((sanitize_filename(filename), content));
+    }
+
+    pub fn to_zip(&self) -> Result<Vec<u8>, Error> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (filename, content) in &self.files {
+            zip.start_file(filename.as_str(), zip::write::SimpleFileOptions::default())?;
+            zip.write_all(content)?;
+        }
+
+        Ok(zip.finish()?.into_inner())
+    }
+
+    pub fn to_tar(&self) -> Result<Vec<u8>, Error> {
+        let mtime = chrono::Utc::now()
+            .timestamp()
+            .try_into()
+            .unwrap_or_default();
+
+        let mut tar = tar::Builder::new(Vec::new());
+        for (filename, content) in &self.files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(mtime);
+            tar.append_data(&mut header, filename, content.as_slice())?;
+        }
+
+        Ok(tar.into_inner()?)
+    }
+
+    pub fn write_to_directory(&self, path: &Path) -> Result<(), Error> {
+        fs::create_dir_all(path)?;
+        for (filename, content) in &self.files {
+            fs::write(path.join(filename), content)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Makes sure a (reporter provided) filename can't escape the bundle directory
+pub fn sanitize_filename(filename: &str) -> String {
+    let filename = filename.replace(['/', '\\'], "_");
+    match filename.strip_prefix('.') {
+        Some(stripped) => format!("_{}", stripped),
+        None => filename,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::codecs::jpeg::JpegEncoder;
+    use image::ImageEncoder;
+    use matrix_sdk::ruma::{owned_event_id, owned_mxc_uri};
+
+    use std::io::{Cursor, Read};
+
+    use super::{Bundle, ImageFormat, ImageProcessing};
+    use crate::MediaFile;
+
+    #[test]
+    fn bundle_archives() {
+        let mut bundle = Bundle::default();
+        bundle.add_file("rendered.md", b"# This Week".to_vec());
+        bundle.add_file("../01234_image.png", vec![0, 1, 2, 3]);
+
+        let mut zip = zip::ZipArchive::new(Cursor::new(bundle.to_zip().unwrap())).unwrap();
+        let mut content = Vec::new();
+        zip.by_name("_._01234_image.png")
+            .unwrap()
+            .read_to_end(&mut content)
+            .unwrap();
+        assert_eq!(content, vec![0, 1, 2, 3]);
+
+        let tar = bundle.to_tar().unwrap();
+        let mut archive = tar::Archive::new(tar.as_slice());
+        let filenames: Vec<String> = archive
+            .entries()
+            .unwrap()
+            .map(|entry| entry.unwrap().path().unwrap().display().to_string())
+            .collect();
+        assert_eq!(filenames, vec!["rendered.md", "_._01234_image.png"]);
+    }
+
+    #[test]
+    fn image_processing() {
+        let mut png = Cursor::new(Vec::new());
+        image::RgbaImage::new(400, 200)
+            .write_to(&mut png, image::ImageFormat::Png)
+            .unwrap();
+        let png = png.into_inner();
+
+        let file = |filename: &str, mimetype: Option<&str>| {
+            let mut file = MediaFile::new(
+                owned_event_id!("$image"),
+                filename.to_owned(),
+                owned_mxc_uri!("mxc://matrix.local/01234"),
+            );
+            file.mimetype = mimetype.map(str::to_owned);
+            file
+        };
+        let screenshot = file("screenshot.png", None);
+        let animation = file("animation.gif", None);
+
+        // Disabled by default
+        let processing = ImageProcessing::default();
+        assert!(!processing.processes(&screenshot));
+        assert_eq!(processing.filename(&screenshot), "01234_screenshot.png");
+
+        let processing = ImageProcessing {
+            max_width: Some(100),
+            format: ImageFormat::Jpeg,
+            ..Default::default()
+        };
+        assert!(processing.processes(&screenshot));
+        assert!(!processing.processes(&animation));
+        assert_eq!(processing.filename(&screenshot), "01234_screenshot.jpg");
+        assert_eq!(processing.filename(&animation), "01234_animation.gif");
+
+        // The MIME type takes precedence over the extension
+        let without_extension = file("screenshot", Some("image/png"));
+        assert!(processing.processes(&without_extension));
+        assert_eq!(
+            processing.filename(&without_extension),
+            "01234_screenshot.jpg"
+        );
+        assert!(!processing.processes(&file("animation.png", Some("image/gif"))));
+        assert!(processing.processes(&file("photo.jpg", Some("application/octet-stream"))));
+
+        let processed = processing.process(&png).unwrap();
+        assert_eq!(
+            image::guess_format(&processed).unwrap(),
+            image::ImageFormat::Jpeg
+        );
+        let processed = image::load_from_memory(&processed).unwrap();
+        assert_eq!((processed.width(), processed.height()), (100, 50));
+    }
+
+    #[test]
+    fn image_processing_orientation() {
+        // EXIF (TIFF, little endian) with a single orientation entry: rotated by 90°
+        let exif = vec![
+            b'I', b'I', 42, 0, 8, 0, 0, 0, // header, offset of the first IFD
+            1, 0, // one entry
+            0x12, 0x01, 3, 0, 1, 0, 0, 0, 6, 0, 0, 0, // orientation (SHORT) = 6
+            0, 0, 0, 0, // no next IFD
+        ];
+        let mut jpeg = Vec::new();
+        let mut encoder = JpegEncoder::new(&mut jpeg);
+        encoder.set_exif_metadata(exif).unwrap();
+        let image = image::RgbImage::new(400, 200);
+        encoder
+            .write_image(&image, 400, 200, image::ExtendedColorType::Rgb8)
+            .unwrap();
+
+        let processing = ImageProcessing {
+            strip_metadata: true,
+            ..Default::default()
+        };
+        let processed = processing.process(&jpeg).unwrap();
+        let processed = image::load_from_memory(&processed).unwrap();
+        assert_eq!((processed.width(), processed.height()), (200, 400));
+    }
+}
@@ -0,0 +1,288 @@
+use chrono::{DateTime, Utc};
+use matrix_sdk::ruma::events::room::message::{
+    ImageMessageEventContent, OriginalSyncRoomMessageEvent, VideoMessageEventContent,
+};
+use matrix_sdk::ruma::events::room::{EncryptedFile, MediaSource};
+use matrix_sdk::ruma::{OwnedEventId, OwnedMxcUri};
+use serde::{Deserialize, Serialize};
+
+use crate::media;
+use crate::utils::MessageEventExt;
+
+/// An image or video attached to a news entry
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(from = "MediaFileData")]
+pub struct MediaFile {
+    /// The event id of the image / video message
+    pub event_id: OwnedEventId,
+    /// When the image / video message was sent, unknown for files stored by older versions
+    pub timestamp: Option<DateTime<Utc>>,
+    pub filename: String,
+    pub mxc_uri: OwnedMxcUri,
+    /// The message body, if it's a caption and not the filename (MSC2530)
+    pub caption: Option<String>,
+    pub mimetype: Option<String>,
+    pub size: Option<u64>,
+    pub width: Option<u64>,
+    pub height: Option<u64>,
+    /// Video duration in milliseconds
+    pub duration: Option<u64>,
+    pub blurhash: Option<String>,
+    pub thumbnail_uri: Option<OwnedMxcUri>,
+    /// Key and hashes of files sent in encrypted rooms
+    pub encryption: Option<Box<EncryptedFile>>,
+}
+
+impl MediaFile {
+    pub fn new(event_id: OwnedEventId, filename: String, mxc_uri: OwnedMxcUri) -> Self {
+        Self {
+            event_id,
+            timestamp: None,
+            filename,
+            mxc_uri,
+            caption: None,
+            mimetype: None,
+            size: None,
+            width: None,
+            height: None,
+            duration: None,
+            blurhash: None,
+            thumbnail_uri: None,
+            encryption: None,
+        }
+    }
+
+    /// The image or video of a message
+    pub fn from_message(event: &OriginalSyncRoomMessageEvent) -> Option<Self> {
+        let event_id = event.event_id.clone();
+        let mut file = if let Some(image) = event.image() {
+            Self::from_image(event_id, image)
+        } else {
+            Self::from_video(event_id, event.video()?)
+        };
+        file.timestamp = event.origin_server_ts.to_system_time().map(Into::into);
+        Some(file)
+    }
+
+    pub fn from_image(event_id: OwnedEventId, image: &ImageMessageEventContent) -> Self {
+        let mut file = Self::from_source(event_id, image.filename(), &image.source);
+        file.caption = image.caption().map(str::to_owned);
+        if let Some(info) = &image.info {
+            file.mimetype = info.mimetype.clone();
+            file.size = info.size.map(u64::from);
+            file.width = info.width.map(u64::from);
+            file.height = info.height.map(u64::from);
+            file.blurhash = info.blurhash.clone();
+            file.thumbnail_uri = plain_uri(info.thumbnail_source.as_ref());
+        }
+
+        file
+    }
+
+    pub fn from_video(event_id: OwnedEventId, video: &VideoMessageEventContent) -> Self {
+        let mut file = Self::from_source(event_id, video.filename(), &video.source);
+        file.caption = video.caption().map(str::to_owned);
+        if let Some(info) = &video.info {
+            file.mimetype = info.mimetype.clone();
+            file.size = info.size.map(u64::from);
+            file.width = info.width.map(u64::from);
+            file.height = info.height.map(u64::from);
+            file.duration = info.duration.map(|d| d.as_millis() as u64);
+            file.blurhash = info.blurhash.clone();
+            file.thumbnail_uri = plain_uri(info.thumbnail_source.as_ref());
+        }
+
+        file
+    }
+
+    fn from_source(event_id: OwnedEventId, filename: &str, source: &MediaSource) -> Self {
+        match source {
+            MediaSource::Plain(mxc_uri) => {
+                Self::new(event_id, filename.to_owned(), mxc_uri.clone())
+            }
+            MediaSource::Encrypted(encrypted) => {
+                let mut file = Self::new(event_id, filename.to_owned(), encrypted.url.clone());
+                file.encryption = Some(encrypted.clone());
+                file
+            }
+        }
+    }
+
+    /// The source to download the file from, decrypting it if necessary
+    pub fn source(&self) -> MediaSource {
+        match &self.encryption {
+            Some(encrypted) => MediaSource::Encrypted(encrypted.clone()),
+            None => MediaSource::Plain(self.mxc_uri.clone()),
+        }
+    }
+
+    /// The filenames aren't guaranteed to be unique ("image.png"), so prefix them with the media id
+    pub fn unique_filename(&self) -> String {
+        let filename = format!(
+            "{}_{}",
+            self.mxc_uri.media_id().unwrap_or_default(),
+            self.filename
+        );
+        media::sanitize_filename(&filename)
+    }
+}
+
+// `EncryptedFile` doesn't implement `PartialEq`, so compare its JSON representation
+impl PartialEq for MediaFile {
+    fn eq(&self, other: &Self) -> bool {
+        serde_json::to_value(self).ok() == serde_json::to_value(other).ok()
+    }
+}
+
+impl Eq for MediaFile {}
+
+fn plain_uri(source: Option<&MediaSource>) -> Option<OwnedMxcUri> {
+    match source {
+        Some(MediaSource::Plain(uri)) => Some(uri.clone()),
+        _ => None,
+    }
+}
+
+/// Older stores saved files as `(event id, filename, mxc uri)` tuples
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MediaFileData {
+    Tuple(OwnedEventId, String, OwnedMxcUri),
+    Struct {
+        event_id: OwnedEventId,
+        timestamp: Option<DateTime<Utc>>,
+        filename: String,
+        mxc_uri: OwnedMxcUri,
+        caption: Option<String>,
+        mimetype: Option<String>,
+        size: Option<u64>,
+        width: Option<u64>,
+        height: Option<u64>,
+        duration: Option<u64>,
+        blurhash: Option<String>,
+        thumbnail_uri: Option<OwnedMxcUri>,
+        encryption: Option<Box<EncryptedFile>>,
+    },
+}
+
+impl From<MediaFileData> for MediaFile {
+    fn from(data: MediaFileData) -> Self {
+        match data {
+            MediaFileData::Tuple(event_id, filename, mxc_uri) => {
+                Self::new(event_id, filename, mxc_uri)
+            }
+            MediaFileData::Struct {
+                event_id,
+                timestamp,
+                filename,
+                mxc_uri,
+                caption,
+                mimetype,
+                size,
+                width,
+                height,
+                duration,
+                blurhash,
+                thumbnail_uri,
+                encryption,
+            } => Self {
+                event_id,
+                timestamp,
+                filename,
+                mxc_uri,
+                caption,
+                mimetype,
+                size,
+                width,
+                height,
+                duration,
+                blurhash,
+                thumbnail_uri,
+                encryption,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches2::assert_matches;
+    use matrix_sdk::ruma::events::room::message::ImageMessageEventContent;
+    use matrix_sdk::ruma::events::room::MediaSource;
+    use matrix_sdk::ruma::owned_event_id;
+    use serde_json::json;
+
+    use super::MediaFile;
+
+    #[test]
+    fn media_file_from_image() {
+        let image: ImageMessageEventContent = serde_json::from_value(json!({
+            "body": "The new preferences dialog",
+            "filename": "screenshot.png",
+            "url": "mxc://matrix.local/01234",
+            "info": {
+                "mimetype": "image/png",
+                "size": 31337,
+                "w": 800,
+                "h": 600,
+                "xyz.amorgan.blurhash": "LEHV6nWB2yk8pyo0adR*.7kCMdnj",
+            },
+        }))
+        .unwrap();
+
+        let file = MediaFile::from_image(owned_event_id!("$image"), &image);
+        assert_eq!(file.filename, "screenshot.png");
+        assert_eq!(file.caption.as_deref(), Some("The new preferences dialog"));
+        assert_eq!(file.mimetype.as_deref(), Some("image/png"));
+        assert_eq!(file.size, Some(31337));
+        assert_eq!((file.width, file.height), (Some(800), Some(600)));
+        assert!(file.blurhash.is_some());
+        assert_eq!(file.unique_filename(), "01234_screenshot.png");
+
+        // Without filename, the body is the filename
+        let image: ImageMessageEventContent = serde_json::from_value(json!({
+            "body": "screenshot.png",
+            "url": "mxc://matrix.local/01234",
+        }))
+        .unwrap();
+
+        let file = MediaFile::from_image(owned_event_id!("$image"), &image);
+        assert_eq!(file.filename, "screenshot.png");
+        assert_eq!(file.caption, None);
+
+        // Files stored by older versions
+        let stored: MediaFile = serde_json::from_value(json!([
+            "$image",
+            "screenshot.png",
+            "mxc://matrix.local/01234"
+        ]))
+        .unwrap();
+        assert_eq!(stored, file);
+
+        // Files sent in encrypted rooms
+        let image: ImageMessageEventContent = serde_json::from_value(json!({
+            "body": "screenshot.png",
+            "file": {
+                "url": "mxc://matrix.local/56789",
+                "key": {
+                    "kty": "oct",
+                    "key_ops": ["encrypt", "decrypt"],
+                    "alg": "A256CTR",
+                    "k": "qcHVMSgYg-71CauWBezXI5qkaRb0LuIy-Wx5kIaHMIA",
+                    "ext": true,
+                },
+                "iv": "X85+XgHN+HEAAAAAAAAAAA",
+                "hashes": { "sha256": "5qG4fFnbbVdlAB1Q72JDKwCagV6Dbkx9uds4rSak37c" },
+                "v": "v2",
+            },
+        }))
+        .unwrap();
+
+        let file = MediaFile::from_image(owned_event_id!("$image"), &image);
+        assert_eq!(file.mxc_uri, "mxc://matrix.local/56789");
+        assert_matches!(file.source(), MediaSource::Encrypted(_));
+        let stored: MediaFile =
+            serde_json::from_value(serde_json::to_value(&file).unwrap()).unwrap();
+        assert_eq!(stored, file);
+    }
+}
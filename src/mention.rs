@@ -0,0 +1,10 @@
+use matrix_sdk::ruma::OwnedUserId;
+use serde::{Deserialize, Serialize};
+
+/// A user mentioned in a news entry
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Mention {
+    pub user_id: OwnedUserId,
+    /// Display name at the time of the report
+    pub display_name: String,
+}
@@ -1,13 +1,13 @@
 use chrono::{DateTime, Utc};
 use matrix_sdk::room::RoomMember;
-use matrix_sdk::ruma::{EventId, OwnedEventId, OwnedMxcUri, OwnedUserId};
+use matrix_sdk::ruma::{EventId, OwnedEventId, OwnedMxcUri, OwnedUserId, UserId};
 use serde::{Deserialize, Serialize};
 
 use std::cell::RefCell;
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::ReactionType;
+use crate::{markdown, EditorEdit, FollowUp, MediaFile, Mention, ReactionType, ReviewState};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct News {
@@ -16,11 +16,38 @@ pub struct News {
     pub reporter_display_name: String,
     pub timestamp: DateTime<Utc>,
     message: RefCell<String>,
+    /// HTML `formatted_body` of the message, if the reporter sent one
+    #[serde(default)]
+    message_html: RefCell<Option<String>>,
+    /// Markdown conversion of `message_html`
+    #[serde(default)]
+    message_markdown: RefCell<Option<String>>,
+    #[serde(default)]
+    mentions: RefCell<Vec<Mention>>,
+    /// Messages the reporter posted in the thread of the news entry
+    #[serde(default)]
+    follow_ups: RefCell<Vec<FollowUp>>,
+    /// Text set by an editor, replacing the reporter's text when rendering
+    #[serde(default)]
+    editor_edit: RefCell<Option<EditorEdit>>,
+    /// Pinned news are shown first in their section / project
+    #[serde(default)]
+    pinned: RefCell<bool>,
+    /// Position within the section / project set by an editor, starting at 1
+    #[serde(default)]
+    position: RefCell<Option<u32>>,
     section_names: RefCell<HashMap<OwnedEventId, String>>,
     project_names: RefCell<HashMap<OwnedEventId, String>>,
-    // <Reaction event id, (file event id, filename, mxc uri)>
-    images: RefCell<HashMap<OwnedEventId, (OwnedEventId, String, OwnedMxcUri)>>,
-    videos: RefCell<HashMap<OwnedEventId, (OwnedEventId, String, OwnedMxcUri)>>,
+    /// <Reaction or `!approve` command event id, editor>
+    #[serde(default)]
+    approvals: RefCell<HashMap<OwnedEventId, OwnedUserId>>,
+    // <Reaction event id, file>
+    images: RefCell<HashMap<OwnedEventId, MediaFile>>,
+    videos: RefCell<HashMap<OwnedEventId, MediaFile>>,
+    /// Reactions whose assignment / approval got removed with an admin command,
+    /// so they are ignored when resyncing or catching up
+    #[serde(default)]
+    dismissed_reactions: RefCell<Vec<OwnedEventId>>,
 }
 
 impl News {
@@ -28,20 +55,69 @@ impl News {
         let reporter_id = reporter.user_id().to_owned();
         let reporter_display_name = reporter.name().to_owned();
 
+        Self::from_parts(
+            event_id,
+            reporter_id,
+            reporter_display_name,
+            chrono::Utc::now(),
+            message,
+        )
+    }
+
+    /// Restore a news entry from previously stored data
+    pub fn from_parts(
+        event_id: OwnedEventId,
+        reporter_id: OwnedUserId,
+        reporter_display_name: String,
+        timestamp: DateTime<Utc>,
+        message: String,
+    ) -> Self {
         Self {
             event_id,
             reporter_id,
             reporter_display_name,
-            timestamp: chrono::Utc::now(),
+            timestamp,
             message: RefCell::new(message),
+            message_html: RefCell::default(),
+            message_markdown: RefCell::default(),
+            mentions: RefCell::default(),
+            follow_ups: RefCell::default(),
+            editor_edit: RefCell::default(),
+            pinned: RefCell::default(),
+            position: RefCell::default(),
             section_names: RefCell::default(),
             project_names: RefCell::default(),
+            approvals: RefCell::default(),
             images: RefCell::default(),
             videos: RefCell::default(),
+            dismissed_reactions: RefCell::default(),
         }
     }
 
+    /// A news entry by `@reporter:matrix.local`, sent now
+    #[cfg(test)]
+    pub fn test_entry(event_id: &str) -> Self {
+        Self::from_parts(
+            event_id.try_into().unwrap(),
+            "@reporter:matrix.local".try_into().unwrap(),
+            "Reporter".to_owned(),
+            Utc::now(),
+            "This is my entry".to_owned(),
+        )
+    }
+
+    /// The message, followed by the follow-up messages as additional paragraphs
     pub fn message(&self) -> String {
+        let mut message = self.original_message();
+        for follow_up in self.follow_ups.borrow().iter() {
+            message.push_str("\n\n");
+            message.push_str(&follow_up.message);
+        }
+        message
+    }
+
+    /// The message of the news submission itself, without follow-ups
+    pub fn original_message(&self) -> String {
         self.message.borrow().clone()
     }
 
@@ -65,6 +141,169 @@ impl News {
         *self.message.borrow_mut() = message;
     }
 
+    /// The HTML formatted message including follow-ups, if any of them is formatted
+    pub fn message_html(&self) -> Option<String> {
+        let follow_ups = self.follow_ups.borrow();
+        if self.message_html.borrow().is_none()
+            && follow_ups.iter().all(|f| f.message_html.is_none())
+        {
+            return None;
+        }
+        if follow_ups.is_empty() {
+            return self.original_message_html();
+        }
+
+        // Every message gets its own block, so they don't run together
+        let parts = [(self.original_message(), self.original_message_html())]
+            .into_iter()
+            .chain(
+                follow_ups
+                    .iter()
+                    .map(|f| (f.message.clone(), f.message_html.clone())),
+            );
+        let html = parts
+            .map(|(message, html)| match html {
+                Some(html) => format!("<div>{}</div>", html),
+                None => plain_to_html(&message),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some(html)
+    }
+
+    pub fn original_message_html(&self) -> Option<String> {
+        self.message_html.borrow().clone()
+    }
+
+    /// The message including follow-ups as Markdown, falls back to the plain text messages
+    pub fn message_markdown(&self) -> String {
+        let mut markdown = self
+            .original_message_markdown()
+            .unwrap_or_else(|| self.original_message());
+        for follow_up in self.follow_ups.borrow().iter() {
+            markdown.push_str("\n\n");
+            markdown.push_str(
+                follow_up
+                    .message_markdown
+                    .as_ref()
+                    .unwrap_or(&follow_up.message),
+            );
+        }
+        markdown
+    }
+
+    pub fn original_message_markdown(&self) -> Option<String> {
+        self.message_markdown.borrow().clone()
+    }
+
+    /// Sets the HTML formatted message, and updates the Markdown conversion of it
+    pub fn set_message_html(&self, html: Option<String>) {
+        let markdown = html.as_deref().map(markdown::html_to_markdown);
+        self.set_formatted_message(html, markdown);
+    }
+
+    /// Restores previously stored HTML / Markdown messages
+    pub fn set_formatted_message(&self, html: Option<String>, markdown: Option<String>) {
+        *self.message_html.borrow_mut() = html;
+        *self.message_markdown.borrow_mut() = markdown;
+    }
+
+    /// Users mentioned in the message
+    pub fn mentions(&self) -> Vec<Mention> {
+        self.mentions.borrow().clone()
+    }
+
+    pub fn set_mentions(&self, mentions: Vec<Mention>) {
+        *self.mentions.borrow_mut() = mentions;
+    }
+
+    pub fn follow_ups(&self) -> Vec<FollowUp> {
+        self.follow_ups.borrow().clone()
+    }
+
+    /// Appends a follow-up message, or replaces it if it got edited
+    pub fn add_follow_up(&self, follow_up: FollowUp) {
+        let mut follow_ups = self.follow_ups.borrow_mut();
+        match follow_ups
+            .iter_mut()
+            .find(|f| f.event_id == follow_up.event_id)
+        {
+            Some(existing) => *existing = follow_up,
+            None => follow_ups.push(follow_up),
+        }
+    }
+
+    pub fn remove_follow_up(&self, event_id: &EventId) -> Option<FollowUp> {
+        let mut follow_ups = self.follow_ups.borrow_mut();
+        let index = follow_ups.iter().position(|f| f.event_id == event_id)?;
+        Some(follow_ups.remove(index))
+    }
+
+    pub fn relates_to_follow_up_id(&self, event_id: &EventId) -> bool {
+        self.follow_ups
+            .borrow()
+            .iter()
+            .any(|f| f.event_id == event_id)
+    }
+
+    pub fn editor_edit(&self) -> Option<EditorEdit> {
+        self.editor_edit.borrow().clone()
+    }
+
+    pub fn set_editor_edit(&self, editor_edit: Option<EditorEdit>) {
+        *self.editor_edit.borrow_mut() = editor_edit;
+    }
+
+    /// Flags the editor's text as outdated after the reporter changed the message.
+    /// Returns `false` if there's no editor's text.
+    pub fn mark_edit_conflict(&self) -> bool {
+        match self.editor_edit.borrow_mut().as_mut() {
+            Some(editor_edit) => {
+                editor_edit.conflict = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The editor's text if there's one, otherwise the reporter's message
+    pub fn edited_message(&self) -> String {
+        match self.editor_edit() {
+            Some(editor_edit) => editor_edit.message,
+            None => self.message(),
+        }
+    }
+
+    pub fn edited_message_html(&self) -> Option<String> {
+        match self.editor_edit() {
+            Some(editor_edit) => Some(plain_to_html(&editor_edit.message)),
+            None => self.message_html(),
+        }
+    }
+
+    pub fn edited_message_markdown(&self) -> String {
+        match self.editor_edit() {
+            Some(editor_edit) => editor_edit.message,
+            None => self.message_markdown(),
+        }
+    }
+
+    pub fn is_pinned(&self) -> bool {
+        *self.pinned.borrow()
+    }
+
+    pub fn set_pinned(&self, pinned: bool) {
+        *self.pinned.borrow_mut() = pinned;
+    }
+
+    pub fn position(&self) -> Option<u32> {
+        *self.position.borrow()
+    }
+
+    pub fn set_position(&self, position: Option<u32>) {
+        *self.position.borrow_mut() = position;
+    }
+
     pub fn is_assigned(&self) -> bool {
         !self.project_names.borrow().is_empty() || !self.section_names.borrow().is_empty()
     }
@@ -91,89 +330,236 @@ impl News {
         self.project_names.borrow_mut().insert(event_id, emoji);
     }
 
-    pub fn images(&self) -> Vec<(String, OwnedMxcUri)> {
-        Self::deduplicate_files(&self.images.borrow())
+    /// Removes the section assignments with the given name, or all of them
+    pub fn remove_section_name(&self, name: Option<&str>) -> bool {
+        self.remove_name(&self.section_names, name)
     }
 
-    pub fn add_image(
+    /// Removes the project assignments with the given name, or all of them
+    pub fn remove_project_name(&self, name: Option<&str>) -> bool {
+        self.remove_name(&self.project_names, name)
+    }
+
+    fn remove_name(
         &self,
-        reaction_event_id: OwnedEventId,
-        image_event_id: OwnedEventId,
-        filename: String,
-        mxc_uri: OwnedMxcUri,
-    ) {
-        self.images
+        names: &RefCell<HashMap<OwnedEventId, String>>,
+        name: Option<&str>,
+    ) -> bool {
+        let removed: Vec<OwnedEventId> = names
+            .borrow()
+            .iter()
+            .filter(|(_, n)| name.is_none_or(|name| name == *n))
+            .map(|(id, _)| id.clone())
+            .collect();
+        names.borrow_mut().retain(|id, _| !removed.contains(id));
+        self.dismiss_reactions(&removed)
+    }
+
+    pub fn add_approval(&self, event_id: OwnedEventId, editor_id: OwnedUserId) {
+        self.approvals.borrow_mut().insert(event_id, editor_id);
+    }
+
+    /// Removes all approvals of the given editor
+    pub fn remove_approvals_by(&self, editor_id: &UserId) -> bool {
+        let removed: Vec<OwnedEventId> = self
+            .approvals
+            .borrow()
+            .iter()
+            .filter(|(_, id)| *id == editor_id)
+            .map(|(reaction_id, _)| reaction_id.clone())
+            .collect();
+        self.approvals
             .borrow_mut()
-            .insert(reaction_event_id, (image_event_id, filename, mxc_uri));
+            .retain(|reaction_id, _| !removed.contains(reaction_id));
+        self.dismiss_reactions(&removed)
     }
 
+    /// Remembers reactions removed with an admin command, returns whether there were any
+    fn dismiss_reactions(&self, reaction_ids: &[OwnedEventId]) -> bool {
+        let mut dismissed = self.dismissed_reactions.borrow_mut();
+        dismissed.extend_from_slice(reaction_ids);
+        dismissed.sort();
+        dismissed.dedup();
+        !reaction_ids.is_empty()
+    }
+
+    /// The distinct editors who approved the news entry
+    pub fn approvers(&self) -> Vec<OwnedUserId> {
+        let mut approvers: Vec<OwnedUserId> = self.approvals.borrow().values().cloned().collect();
+        approvers.sort();
+        approvers.dedup();
+        approvers
+    }
+
+    /// Approvals, keyed by reaction (or `!approve` command) event id
+    pub fn approval_reactions(&self) -> HashMap<OwnedEventId, OwnedUserId> {
+        self.approvals.borrow().clone()
+    }
+
+    pub fn review_state(&self, required_approvals: usize) -> ReviewState {
+        let approvals = self.approvers().len();
+        if !self.is_assigned() {
+            ReviewState::Submitted
+        } else if approvals < required_approvals {
+            ReviewState::Assigned {
+                approvals,
+                required: required_approvals,
+            }
+        } else {
+            ReviewState::Reviewed
+        }
+    }
+
+    /// Section assignments, keyed by reaction (or `!assign` command) event id
+    pub fn section_reactions(&self) -> HashMap<OwnedEventId, String> {
+        self.section_names.borrow().clone()
+    }
+
+    /// Project assignments, keyed by reaction (or `!assign` command) event id
+    pub fn project_reactions(&self) -> HashMap<OwnedEventId, String> {
+        self.project_names.borrow().clone()
+    }
+
+    /// Images, keyed by reaction event id
+    pub fn image_reactions(&self) -> HashMap<OwnedEventId, MediaFile> {
+        self.images.borrow().clone()
+    }
+
+    /// Reactions whose assignment / approval got removed with an admin command
+    pub fn dismissed_reactions(&self) -> Vec<OwnedEventId> {
+        self.dismissed_reactions.borrow().clone()
+    }
+
+    pub fn add_dismissed_reaction(&self, reaction_id: OwnedEventId) {
+        self.dismiss_reactions(&[reaction_id]);
+    }
+
+    /// Videos, keyed by reaction event id
+    pub fn video_reactions(&self) -> HashMap<OwnedEventId, MediaFile> {
+        self.videos.borrow().clone()
+    }
+
+    /// Images as (unique filename, mxc uri)
+    pub fn images(&self) -> Vec<(String, OwnedMxcUri)> {
+        Self::file_names(self.image_files())
+    }
+
+    pub fn image_files(&self) -> Vec<MediaFile> {
+        Self::deduplicate_files(&self.images.borrow())
+    }
+
+    pub fn add_image(&self, reaction_event_id: OwnedEventId, image: MediaFile) {
+        self.images.borrow_mut().insert(reaction_event_id, image);
+    }
+
+    /// Videos as (unique filename, mxc uri)
     pub fn videos(&self) -> Vec<(String, OwnedMxcUri)> {
+        Self::file_names(self.video_files())
+    }
+
+    pub fn video_files(&self) -> Vec<MediaFile> {
         Self::deduplicate_files(&self.videos.borrow())
     }
 
-    pub fn add_video(
-        &self,
-        reaction_event_id: OwnedEventId,
-        video_event_id: OwnedEventId,
-        filename: String,
-        mxc_uri: OwnedMxcUri,
-    ) {
-        self.videos
-            .borrow_mut()
-            .insert(reaction_event_id, (video_event_id, filename, mxc_uri));
+    pub fn add_video(&self, reaction_event_id: OwnedEventId, video: MediaFile) {
+        self.videos.borrow_mut().insert(reaction_event_id, video);
     }
 
-    /// Remove a image or video file from this news
-    pub fn remove_file(
+    /// Rebuilds the project / section assignments and approvals from the current reactions
+    /// to the message, keeping the ones made with the given admin command events.
+    /// Dismissed reactions are skipped.
+    pub fn resync_assignments(
         &self,
-        file_event_id: &OwnedEventId,
-    ) -> Option<(OwnedEventId, String, OwnedMxcUri)> {
-        let mut removed: Option<(OwnedEventId, String, OwnedMxcUri)> = None;
+        reactions: &[(OwnedEventId, OwnedUserId, ReactionType)],
+        command_ids: &[OwnedEventId],
+    ) {
+        self.section_names
+            .borrow_mut()
+            .retain(|id, _| command_ids.contains(id));
+        self.project_names
+            .borrow_mut()
+            .retain(|id, _| command_ids.contains(id));
+        self.approvals
+            .borrow_mut()
+            .retain(|id, _| command_ids.contains(id));
 
-        self.images.borrow_mut().retain(|_k, v| {
-            if v.0 != *file_event_id {
-                removed = Some((v.0.clone(), v.1.clone(), v.2.clone()));
-                true
-            } else {
-                false
+        let dismissed = self.dismissed_reactions();
+        for (reaction_id, sender, reaction_type) in reactions {
+            if dismissed.contains(reaction_id) {
+                continue;
             }
-        });
 
-        self.videos.borrow_mut().retain(|_k, v| {
-            if v.0 != *file_event_id {
-                removed = Some((v.0.clone(), v.1.clone(), v.2.clone()));
-                true
-            } else {
-                false
+            match reaction_type {
+                ReactionType::Section(Some(section)) => {
+                    self.add_section_name(reaction_id.clone(), section.name.clone())
+                }
+                ReactionType::Project(Some(project)) => {
+                    self.add_project_name(reaction_id.clone(), project.name.clone())
+                }
+                ReactionType::Review => self.add_approval(reaction_id.clone(), sender.clone()),
+                _ => (),
             }
-        });
+        }
+    }
+
+    /// Replaces all images and videos, keyed by reaction event id
+    pub fn set_files(
+        &self,
+        images: HashMap<OwnedEventId, MediaFile>,
+        videos: HashMap<OwnedEventId, MediaFile>,
+    ) {
+        *self.images.borrow_mut() = images;
+        *self.videos.borrow_mut() = videos;
+    }
+
+    /// Remove a image or video file from this news
+    pub fn remove_file(&self, file_event_id: &EventId) -> Option<MediaFile> {
+        let mut removed: Option<MediaFile> = None;
+
+        for files in [&self.images, &self.videos] {
+            files.borrow_mut().retain(|_k, file| {
+                if file.event_id != file_event_id {
+                    true
+                } else {
+                    removed = Some(file.clone());
+                    false
+                }
+            });
+        }
 
         removed
     }
 
-    /// Deduplicates files based on the mxc uri.
+    /// Deduplicates files based on the mxc uri, ordered by the time they were sent.
     /// Can happen when multiple editors reacted to the same file
     /// -> File is listed for every single reaction
-    fn deduplicate_files(
-        files: &HashMap<OwnedEventId, (OwnedEventId, String, OwnedMxcUri)>,
-    ) -> Vec<(String, OwnedMxcUri)> {
-        let mut deduplicated = HashMap::new();
-
-        for (_event_id, filename, mxc_uri) in files.values() {
-            // The filenames aren't guaranteed to be unique ("image.png"), so prefix them with the media id
-            let unique_name = format!("{}_{}", mxc_uri.media_id().unwrap_or_default(), filename);
+    fn deduplicate_files(files: &HashMap<OwnedEventId, MediaFile>) -> Vec<MediaFile> {
+        let mut deduplicated: Vec<MediaFile> = files.values().cloned().collect();
+        deduplicated.sort_by(|a, b| (a.timestamp, &a.event_id).cmp(&(b.timestamp, &b.event_id)));
 
-            deduplicated.insert(mxc_uri.clone(), (unique_name, mxc_uri.clone()));
-        }
+        let mut mxc_uris = HashSet::new();
+        deduplicated.retain(|file| mxc_uris.insert(file.mxc_uri.clone()));
+        deduplicated
+    }
 
-        deduplicated.values().cloned().collect()
+    fn file_names(files: Vec<MediaFile>) -> Vec<(String, OwnedMxcUri)> {
+        files
+            .into_iter()
+            .map(|file| (file.unique_filename(), file.mxc_uri))
+            .collect()
     }
 
     pub fn remove_reaction_id(&self, event_id: &EventId) -> ReactionType {
+        self.dismissed_reactions
+            .borrow_mut()
+            .retain(|id| id != event_id);
+
         if self.section_names.borrow_mut().remove(event_id).is_some() {
             ReactionType::Section(None)
         } else if self.project_names.borrow_mut().remove(event_id).is_some() {
             ReactionType::Project(None)
+        } else if self.approvals.borrow_mut().remove(event_id).is_some() {
+            ReactionType::Review
         } else if self.images.borrow_mut().remove(event_id).is_some()
             || self.videos.borrow_mut().remove(event_id).is_some()
         {
@@ -194,6 +580,11 @@ impl News {
                 return true;
             }
         }
+        for i in self.approvals.borrow().keys() {
+            if i == reaction_id {
+                return true;
+            }
+        }
         for i in self.images.borrow().keys() {
             if i == reaction_id {
                 return true;
@@ -204,17 +595,22 @@ impl News {
                 return true;
             }
         }
+        for i in self.dismissed_reactions.borrow().iter() {
+            if i == reaction_id {
+                return true;
+            }
+        }
 
         false
     }
 
     /// Check if a image/video belongs to this news using the file message id
     pub fn relates_to_file_id(&self, file_id: &EventId) -> bool {
-        if self.images.borrow().values().any(|v| v.0 == file_id) {
+        if self.images.borrow().values().any(|f| f.event_id == file_id) {
             return true;
         }
 
-        if self.videos.borrow().values().any(|v| v.0 == file_id) {
+        if self.videos.borrow().values().any(|f| f.event_id == file_id) {
             return true;
         }
 
@@ -222,6 +618,16 @@ impl News {
     }
 }
 
+/// Escapes a plain text message, so it can be joined with HTML formatted messages
+fn plain_to_html(message: &str) -> String {
+    let escaped = message
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\n', "<br>");
+    format!("<p>{}</p>", escaped)
+}
+
 impl PartialOrd for News {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -233,3 +639,182 @@ impl Ord for News {
         self.timestamp.cmp(&other.timestamp)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Project, Section};
+    use chrono::TimeZone;
+    use matrix_sdk::ruma::{owned_event_id, owned_mxc_uri, owned_user_id};
+
+    #[test]
+    fn resync_assignments() {
+        let news = News::test_entry("$news");
+        news.add_section_name(owned_event_id!("$kept_reaction"), "apps".to_string());
+        news.add_section_name(owned_event_id!("$assign"), "events".to_string());
+        news.add_project_name(owned_event_id!("$redacted_reaction"), "hebbot".to_string());
+        news.add_approval(
+            owned_event_id!("$approve"),
+            owned_user_id!("@editor:example.org"),
+        );
+        news.add_approval(
+            owned_event_id!("$redacted_approval"),
+            owned_user_id!("@other:example.org"),
+        );
+
+        let section = |name: &str| Section {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        let project = Project {
+            name: "fractal".to_string(),
+            ..Default::default()
+        };
+        let reactions = [
+            (
+                owned_event_id!("$kept_reaction"),
+                owned_user_id!("@editor:example.org"),
+                ReactionType::Section(Some(section("apps"))),
+            ),
+            (
+                owned_event_id!("$missed_reaction"),
+                owned_user_id!("@editor:example.org"),
+                ReactionType::Project(Some(project)),
+            ),
+            (
+                owned_event_id!("$missed_approval"),
+                owned_user_id!("@other:example.org"),
+                ReactionType::Review,
+            ),
+            (
+                owned_event_id!("$notice"),
+                owned_user_id!("@reporter:matrix.local"),
+                ReactionType::Notice,
+            ),
+        ];
+        news.resync_assignments(
+            &reactions,
+            &[owned_event_id!("$assign"), owned_event_id!("$approve")],
+        );
+
+        assert_eq!(news.section_names(), ["apps", "events"]);
+        assert_eq!(news.project_names(), ["fractal"]);
+        assert_eq!(
+            news.approvers(),
+            [
+                owned_user_id!("@editor:example.org"),
+                owned_user_id!("@other:example.org")
+            ]
+        );
+        assert!(!news.relates_to_reaction_id(&owned_event_id!("$redacted_reaction")));
+        assert!(!news.relates_to_reaction_id(&owned_event_id!("$redacted_approval")));
+        assert!(!news.relates_to_reaction_id(&owned_event_id!("$notice")));
+    }
+
+    #[test]
+    fn image_order() {
+        let news = News::test_entry("$news");
+        let image = |event_id: OwnedEventId, mxc_uri: OwnedMxcUri, minute: Option<u32>| {
+            let mut file = MediaFile::new(event_id, "image.png".to_string(), mxc_uri);
+            file.timestamp =
+                minute.map(|minute| Utc.with_ymd_and_hms(2024, 5, 1, 12, minute, 0).unwrap());
+            file
+        };
+        let second = image(
+            owned_event_id!("$second"),
+            owned_mxc_uri!("mxc://example.org/second"),
+            Some(2),
+        );
+        let first = image(
+            owned_event_id!("$first"),
+            owned_mxc_uri!("mxc://example.org/first"),
+            Some(1),
+        );
+        let stored = image(
+            owned_event_id!("$stored"),
+            owned_mxc_uri!("mxc://example.org/stored"),
+            None,
+        );
+        news.add_image(owned_event_id!("$reaction_a"), second.clone());
+        news.add_image(owned_event_id!("$reaction_b"), first.clone());
+        news.add_image(owned_event_id!("$reaction_c"), second.clone());
+        news.add_image(owned_event_id!("$reaction_d"), stored.clone());
+
+        // Files of older stores without timestamp come first, reactions to the same file are merged
+        assert_eq!(news.image_files(), [stored, first, second]);
+    }
+
+    #[test]
+    fn dismissed_reactions() {
+        let news = News::test_entry("$news");
+        let editor = owned_user_id!("@editor:example.org");
+        news.add_section_name(owned_event_id!("$section_reaction"), "apps".to_string());
+        news.add_project_name(owned_event_id!("$project_reaction"), "hebbot".to_string());
+        news.add_approval(owned_event_id!("$review_reaction"), editor.clone());
+
+        // `!unassign` and `!unapprove`
+        assert!(news.remove_section_name(Some("apps")));
+        assert!(!news.remove_project_name(Some("fractal")));
+        assert!(news.remove_approvals_by(&editor));
+        assert_eq!(
+            news.dismissed_reactions(),
+            [
+                owned_event_id!("$review_reaction"),
+                owned_event_id!("$section_reaction")
+            ]
+        );
+
+        // Replayed reactions are already known, and resyncing doesn't restore them
+        assert!(news.relates_to_reaction_id(&owned_event_id!("$section_reaction")));
+        let section = Section {
+            name: "apps".to_string(),
+            ..Default::default()
+        };
+        news.resync_assignments(
+            &[
+                (
+                    owned_event_id!("$section_reaction"),
+                    editor.clone(),
+                    ReactionType::Section(Some(section)),
+                ),
+                (
+                    owned_event_id!("$review_reaction"),
+                    editor,
+                    ReactionType::Review,
+                ),
+            ],
+            &[],
+        );
+        assert!(news.section_names().is_empty());
+        assert!(news.approvers().is_empty());
+
+        // Removing the reaction from the room makes the dismissal obsolete
+        news.remove_reaction_id(&owned_event_id!("$section_reaction"));
+        assert_eq!(
+            news.dismissed_reactions(),
+            [owned_event_id!("$review_reaction")]
+        );
+    }
+
+    #[test]
+    fn message_html_with_follow_ups() {
+        let news = News::test_entry("$news");
+        news.set_message_html(Some("Some <b>news</b>".to_string()));
+        assert_eq!(news.message_html().unwrap(), "Some <b>news</b>");
+
+        news.add_follow_up(FollowUp::new(
+            owned_event_id!("$plain"),
+            "More & more".to_string(),
+            None,
+        ));
+        news.add_follow_up(FollowUp::new(
+            owned_event_id!("$html"),
+            "Even more".to_string(),
+            Some("Even <em>more</em>".to_string()),
+        ));
+        assert_eq!(
+            news.message_html().unwrap(),
+            "<div>Some <b>news</b></div>\n<p>More &amp; more</p>\n<div>Even <em>more</em></div>"
+        );
+    }
+}
@@ -1,51 +1,132 @@
-use chrono::{DateTime, Utc};
-use matrix_sdk::ruma::{EventId, OwnedEventId};
+use chrono::{DateTime, Duration, Utc};
+use matrix_sdk::ruma::{EventId, OwnedEventId, UserId};
 
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::Read;
-use std::{env, fs};
+use std::env;
+use std::path::{Path, PathBuf};
 
-use crate::{Error, News};
+use crate::{Edition, Error, News, RenderedOutput};
+
+mod json;
+mod sqlite;
+
+pub use json::JsonBackend;
+pub use sqlite::SqliteBackend;
+
+/// Persistence layer used by the `NewsStore`
+pub trait StoreBackend: Send {
+    /// Load all stored news entries
+    fn load(&self) -> Result<HashMap<OwnedEventId, News>, Error>;
+
+    /// Persist a new or changed news entry
+    fn upsert_news(&self, news: &News) -> Result<(), Error>;
+
+    /// Remove a news entry
+    fn delete_news(&self, event_id: &EventId) -> Result<(), Error>;
+
+    /// Remove all news entries, e.g. after they got archived
+    fn clear_news(&self) -> Result<(), Error>;
+
+    /// Load all archived editions
+    fn load_editions(&self) -> Result<Vec<Edition>, Error>;
+
+    /// Persist the given editions, replacing everything stored before
+    fn save_editions(&self, editions: &[Edition]) -> Result<(), Error>;
+}
+
+/// The result of associating an image / video with a news entry
+#[derive(Debug, PartialEq, Eq)]
+pub enum RelatedNews<'a> {
+    Found(&'a News),
+    /// Candidates, sorted by their time distance to the file
+    Ambiguous(Vec<&'a News>),
+    None,
+}
 
 pub struct NewsStore {
     news_map: HashMap<OwnedEventId, News>,
+    editions: Vec<Edition>,
+    backend: Box<dyn StoreBackend>,
 }
 
 impl NewsStore {
-    pub fn read() -> Self {
-        // Try to open+read store.json
-        let path = Self::get_path();
-        debug!("Trying to read stored news file from path: {:?}", path);
-
-        let news_map = if let Ok(mut file) = File::open(path) {
-            let mut data = String::new();
-            file.read_to_string(&mut data)
-                .expect("Unable to read news store file");
-            serde_json::from_str(&data).expect("Unable to parse news store file")
-        } else {
-            warn!("Unable to open news store file");
-            HashMap::new()
+    /// Opens the store backend selected by the `STORE_BACKEND` env variable
+    /// (`json` or `sqlite`) at the `STORE_PATH` location.
+    /// The SQLite backend imports the JSON store next to the database (or at `STORE_IMPORT_PATH`).
+    pub fn read() -> Result<Self, Error> {
+        let backend_name = env::var("STORE_BACKEND").unwrap_or("json".into());
+
+        let backend: Box<dyn StoreBackend> = match backend_name.as_str() {
+            "json" => {
+                let path = env::var("STORE_PATH").unwrap_or("./store.json".into());
+                Box::new(JsonBackend::new(path))
+            }
+            "sqlite" => {
+                // Installations switching from the JSON backend often keep
+                // `STORE_PATH=/data/store.json`, the database is created next to it then
+                let path = match env::var("STORE_PATH").map(PathBuf::from) {
+                    Ok(path) if path.extension().is_some_and(|e| e == "json") => {
+                        path.with_extension("db")
+                    }
+                    Ok(path) => path,
+                    Err(_) => PathBuf::from("./store.db"),
+                };
+                let backend = SqliteBackend::open(&path)?;
+
+                // Migrate data of previous JSON based installations
+                let import_path = env::var("STORE_IMPORT_PATH")
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|_| path.with_extension("json"));
+                backend.import_json(import_path)?;
+
+                Box::new(backend)
+            }
+            _ => return Err(Error::UnknownStoreBackend(backend_name)),
+        };
+
+        Self::with_backend(backend)
+    }
+
+    /// Opens an existing store file for reading, the backend is selected by the file extension
+    /// (`.db` / `.sqlite` for SQLite, JSON otherwise)
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        let backend: Box<dyn StoreBackend> = match path.extension().and_then(|e| e.to_str()) {
+            Some("db" | "sqlite") => Box::new(SqliteBackend::open_read_only(path)?),
+            _ => Box::new(JsonBackend::new(path)),
         };
 
-        Self { news_map }
+        Self::with_backend(backend)
+    }
+
+    pub fn with_backend(backend: Box<dyn StoreBackend>) -> Result<Self, Error> {
+        let news_map = backend.load()?;
+        let mut editions = backend.load_editions()?;
+        editions.sort_by_key(|edition| edition.number);
+
+        Ok(Self {
+            news_map,
+            editions,
+            backend,
+        })
     }
 
-    pub fn add_news(&mut self, news: News) {
+    pub fn add_news(&mut self, news: News) -> Result<(), Error> {
         debug!("Store {:#?}", &news);
 
+        self.backend.upsert_news(&news)?;
         self.news_map.insert(news.event_id.clone(), news);
-        self.write_data();
+        Ok(())
     }
 
     pub fn remove_news(&mut self, event_id: &EventId) -> Result<News, Error> {
-        if let Some(news) = self.news_map.remove(event_id) {
-            debug!("Removed {:#?}", &news);
-            self.write_data();
-            return Ok(news);
+        if !self.news_map.contains_key(event_id) {
+            return Err(Error::NewsEventIdNotFound);
         }
 
-        Err(Error::NewsEventIdNotFound)
+        self.backend.delete_news(event_id)?;
+        let news = self.news_map.remove(event_id).unwrap();
+        debug!("Removed {:#?}", &news);
+        Ok(news)
     }
 
     pub fn news(&self) -> Vec<News> {
@@ -57,6 +138,23 @@ impl NewsStore {
         self.news_map.get(message_event_id)
     }
 
+    /// The news sorted by timestamp. The position (starting at 1) is the short
+    /// reference of a news entry in admin commands, as shown by `!status`
+    pub fn numbered_news(&self) -> Vec<&News> {
+        let mut news: Vec<&News> = self.news_map.values().collect();
+        news.sort_by(|a, b| (a.timestamp, &a.event_id).cmp(&(b.timestamp, &b.event_id)));
+        news
+    }
+
+    /// Get news by using its short reference (see `numbered_news`) or its message event id
+    pub fn news_by_ref(&self, reference: &str) -> Option<&News> {
+        let reference = reference.trim().trim_start_matches('#');
+        match reference.parse::<usize>() {
+            Ok(number) => self.numbered_news().get(number.checked_sub(1)?).copied(),
+            Err(_) => self.news_map.get(<&EventId>::try_from(reference).ok()?),
+        }
+    }
+
     /// Get news by using reaction event id
     pub fn news_by_reaction_id(&self, reaction_event_id: &EventId) -> Option<&News> {
         self.news_map
@@ -71,52 +169,360 @@ impl NewsStore {
             .find(|&news| news.relates_to_file_id(file_id))
     }
 
-    pub fn find_related_news(&self, reporter_id: &str, timestamp: &DateTime<Utc>) -> Option<&News> {
-        let mut shortest_time_diff = None;
-        let mut related_news = None;
+    /// Get news by using the event id of a follow-up message in its thread
+    pub fn news_by_follow_up_id(&self, event_id: &EventId) -> Option<&News> {
+        self.news_map
+            .values()
+            .find(|&news| news.relates_to_follow_up_id(event_id))
+    }
+
+    /// Whether the event is already part of a news entry, e.g. when catching up on missed events
+    pub fn contains_event(&self, event_id: &EventId) -> bool {
+        self.news_by_message_id(event_id).is_some()
+            || self.news_by_follow_up_id(event_id).is_some()
+            || self.news_by_reaction_id(event_id).is_some()
+            || self.news_by_file_id(event_id).is_some()
+    }
+
+    /// Finds the news entry an image / video belongs to.
+    ///
+    /// News entries referenced by a reply or thread relation of the file message take precedence.
+    /// Otherwise the reporter's news entries posted within the `window` are candidates,
+    /// the match is ambiguous if the closest one isn't clearly closer than the others.
+    pub fn find_related_news(
+        &self,
+        reporter_id: &UserId,
+        timestamp: &DateTime<Utc>,
+        relations: &[&EventId],
+        window: Duration,
+    ) -> RelatedNews<'_> {
+        if let Some(news) = relations.iter().find_map(|id| self.news_map.get(*id)) {
+            return RelatedNews::Found(news);
+        }
+
+        let mut candidates: Vec<(Duration, &News)> = self
+            .news_map
+            .values()
+            .filter(|news| news.reporter_id == reporter_id)
+            .map(|news| ((news.timestamp - *timestamp).abs(), news))
+            .filter(|(distance, _)| *distance <= window)
+            .collect();
+        candidates.sort_by_key(|(distance, news)| (*distance, news.timestamp));
 
-        for news in self.news_map.values() {
-            if news.reporter_id != reporter_id {
-                continue;
+        match candidates.as_slice() {
+            [] => RelatedNews::None,
+            [(_, news)] => RelatedNews::Found(news),
+            [(closest, news), (second, _), ..] if *closest * 2 < *second => {
+                RelatedNews::Found(news)
             }
+            _ => RelatedNews::Ambiguous(candidates.into_iter().map(|(_, news)| news).collect()),
+        }
+    }
+
+    /// All archived editions, sorted by number
+    pub fn editions(&self) -> &[Edition] {
+        &self.editions
+    }
 
-            let time_diff = (news.timestamp.time() - timestamp.time())
-                .num_seconds()
-                .abs();
+    pub fn edition(&self, number: u32) -> Option<&Edition> {
+        self.editions.iter().find(|e| e.number == number)
+    }
 
-            if shortest_time_diff.is_none() {
-                related_news = Some(news);
-                shortest_time_diff = Some(time_diff);
-                continue;
+    /// Snapshots the current news into the open edition,
+    /// or into a new one if all editions are closed already
+    pub fn publish_edition(
+        &mut self,
+        editor_id: &UserId,
+        editor_display_name: &str,
+        outputs: Vec<RenderedOutput>,
+    ) -> Result<u32, Error> {
+        let news = self.sorted_news();
+        let mut editions = self.editions.clone();
+        let number = match editions.iter_mut().find(|e| !e.closed) {
+            Some(edition) => {
+                edition.timestamp = Utc::now();
+                edition.editor_id = editor_id.to_owned();
+                edition.editor_display_name = editor_display_name.to_owned();
+                edition.outputs = outputs;
+                edition.news = news;
+                edition.number
             }
+            None => push_edition(
+                &mut editions,
+                editor_id,
+                editor_display_name,
+                outputs,
+                false,
+                news,
+            ),
+        };
 
-            if time_diff < shortest_time_diff.unwrap() {
-                related_news = Some(news);
-                shortest_time_diff = Some(time_diff);
+        self.write_editions(editions)?;
+        Ok(number)
+    }
+
+    /// Moves all news entries into the open edition (or a new one) and closes it.
+    /// The news snapshot of a published edition is kept, so it can still be rerendered.
+    /// Returns the edition number, or `None` if there was nothing to archive.
+    pub fn archive_news(
+        &mut self,
+        editor_id: &UserId,
+        editor_display_name: &str,
+    ) -> Result<Option<u32>, Error> {
+        let news = self.sorted_news();
+        let mut editions = self.editions.clone();
+        let number = match editions.iter_mut().find(|e| !e.closed) {
+            Some(edition) => {
+                edition.closed = true;
+                if edition.news != news {
+                    edition.cleared_news = news;
+                }
+                Some(edition.number)
             }
+            None if news.is_empty() => None,
+            None => Some(push_edition(
+                &mut editions,
+                editor_id,
+                editor_display_name,
+                Vec::new(),
+                true,
+                news,
+            )),
+        };
+
+        // The news are only removed once they are archived
+        self.write_editions(editions)?;
+        self.backend.clear_news()?;
+        self.news_map.clear();
+        Ok(number)
+    }
+
+    fn sorted_news(&self) -> Vec<News> {
+        let mut news = self.news();
+        news.sort();
+        news
+    }
+
+    /// Writes a changed news entry to the store backend
+    pub fn write_news(&self, event_id: &EventId) -> Result<(), Error> {
+        let Some(news) = self.news_map.get(event_id) else {
+            return Ok(());
+        };
+
+        debug!("Writing news entry {}…", event_id);
+        self.backend.upsert_news(news)
+    }
+
+    /// Writes the changed editions archive to the store backend, and keeps it if that succeeded
+    fn write_editions(&mut self, editions: Vec<Edition>) -> Result<(), Error> {
+        debug!("Writing editions…");
+        self.backend.save_editions(&editions)?;
+        self.editions = editions;
+        Ok(())
+    }
+}
+
+fn push_edition(
+    editions: &mut Vec<Edition>,
+    editor_id: &UserId,
+    editor_display_name: &str,
+    outputs: Vec<RenderedOutput>,
+    closed: bool,
+    news: Vec<News>,
+) -> u32 {
+    let number = editions.last().map(|e| e.number + 1).unwrap_or(1);
+    editions.push(Edition {
+        number,
+        timestamp: Utc::now(),
+        editor_id: editor_id.to_owned(),
+        editor_display_name: editor_display_name.to_owned(),
+        outputs,
+        closed,
+        news,
+        cleared_news: Vec::new(),
+    });
+    number
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::{DateTime, Duration, Utc};
+    use matrix_sdk::ruma::{event_id, owned_user_id, user_id, EventId, OwnedEventId};
+
+    use super::{NewsStore, RelatedNews, SqliteBackend, StoreBackend};
+    use crate::{Edition, Error, News};
+
+    /// Loads the given news entries, but fails to write anything
+    struct ReadOnlyBackend(HashMap<OwnedEventId, News>);
+
+    impl StoreBackend for ReadOnlyBackend {
+        fn load(&self) -> Result<HashMap<OwnedEventId, News>, Error> {
+            Ok(self.0.clone())
+        }
+
+        fn upsert_news(&self, _news: &News) -> Result<(), Error> {
+            Err(read_only())
+        }
+
+        fn delete_news(&self, _event_id: &EventId) -> Result<(), Error> {
+            Err(read_only())
+        }
+
+        fn clear_news(&self) -> Result<(), Error> {
+            Err(read_only())
+        }
+
+        fn load_editions(&self) -> Result<Vec<Edition>, Error> {
+            Ok(Vec::new())
         }
 
-        related_news
+        fn save_editions(&self, _editions: &[Edition]) -> Result<(), Error> {
+            Err(read_only())
+        }
     }
 
-    /// Wipes all news entries
-    pub fn clear_news(&mut self) {
-        self.news_map.clear();
-        self.write_data();
+    fn read_only() -> Error {
+        Error::Io(std::io::ErrorKind::PermissionDenied.into())
     }
 
-    /// Writes data as JSON to disk
-    pub fn write_data(&self) {
-        debug!("Writing data…");
-        let json = serde_json::to_string_pretty(&self.news_map).unwrap();
-        let path = Self::get_path();
-        fs::write(path, json).expect("Unable to write news store");
+    fn news(event_id: &str, reporter: &str, timestamp: DateTime<Utc>) -> News {
+        let mut news = News::test_entry(event_id);
+        news.reporter_id = reporter.try_into().unwrap();
+        news.timestamp = timestamp;
+        news
     }
 
-    fn get_path() -> String {
-        match env::var("STORE_PATH") {
-            Ok(val) => val,
-            Err(_) => "./store.json".to_string(),
+    #[test]
+    fn find_related_news() {
+        let backend = SqliteBackend::open(":memory:").unwrap();
+        let mut store = NewsStore::with_backend(Box::new(backend)).unwrap();
+        let reporter = user_id!("@reporter:matrix.local");
+        let now = Utc::now();
+        let window = Duration::hours(24);
+
+        store
+            .add_news(news("$first", reporter.as_str(), now - Duration::days(3)))
+            .unwrap();
+        store
+            .add_news(news("$other", "@other:matrix.local", now))
+            .unwrap();
+
+        // Same time of day, but three days earlier
+        let found = store.find_related_news(reporter, &now, &[], window);
+        assert_eq!(found, RelatedNews::None);
+
+        store
+            .add_news(news("$second", reporter.as_str(), now - Duration::hours(5)))
+            .unwrap();
+        store
+            .add_news(news(
+                "$third",
+                reporter.as_str(),
+                now - Duration::minutes(2),
+            ))
+            .unwrap();
+        let found = store.find_related_news(reporter, &now, &[], window);
+        assert!(matches!(found, RelatedNews::Found(news) if news.event_id == "$third"));
+
+        // Posted between two news entries
+        let timestamp = now - Duration::hours(2);
+        match store.find_related_news(reporter, &timestamp, &[], window) {
+            RelatedNews::Ambiguous(candidates) => {
+                let ids: Vec<&str> = candidates.iter().map(|n| n.event_id.as_str()).collect();
+                assert_eq!(ids, vec!["$third", "$second"]);
+            }
+            related => panic!("Expected ambiguous match, got {:?}", related),
         }
+
+        // Replies and threads take precedence
+        let relations = [event_id!("$unknown"), event_id!("$first")];
+        let found = store.find_related_news(reporter, &timestamp, &relations, window);
+        assert!(matches!(found, RelatedNews::Found(news) if news.event_id == "$first"));
+
+        let other = owned_user_id!("@other:matrix.local");
+        let found = store.find_related_news(&other, &now, &[], window);
+        assert!(matches!(found, RelatedNews::Found(news) if news.event_id == "$other"));
+    }
+
+    #[test]
+    fn news_by_ref() {
+        let backend = SqliteBackend::open(":memory:").unwrap();
+        let mut store = NewsStore::with_backend(Box::new(backend)).unwrap();
+        let now = Utc::now();
+
+        store
+            .add_news(news("$second", "@reporter:matrix.local", now))
+            .unwrap();
+        store
+            .add_news(news(
+                "$first",
+                "@reporter:matrix.local",
+                now - Duration::hours(1),
+            ))
+            .unwrap();
+
+        let event_id = |reference| store.news_by_ref(reference).map(|n| n.event_id.as_str());
+        assert_eq!(event_id("1"), Some("$first"));
+        assert_eq!(event_id("#2"), Some("$second"));
+        assert_eq!(event_id("$second"), Some("$second"));
+        assert_eq!(event_id("0"), None);
+        assert_eq!(event_id("3"), None);
+    }
+
+    #[test]
+    fn archive_published_edition() {
+        let backend = SqliteBackend::open(":memory:").unwrap();
+        let mut store = NewsStore::with_backend(Box::new(backend)).unwrap();
+        let editor = user_id!("@editor:matrix.local");
+        let now = Utc::now();
+
+        store
+            .add_news(news("$first", "@reporter:matrix.local", now))
+            .unwrap();
+        let number = store.publish_edition(editor, "Editor", Vec::new()).unwrap();
+        let published = store.edition(number).unwrap().news.clone();
+
+        // Changes after publishing don't end up in the published snapshot
+        store
+            .add_news(news("$second", "@reporter:matrix.local", now))
+            .unwrap();
+        store.news_by_ref("$first").unwrap().set_pinned(true);
+        assert_eq!(store.archive_news(editor, "Editor").unwrap(), Some(number));
+
+        let edition = store.edition(number).unwrap();
+        assert!(edition.closed);
+        assert_eq!(edition.news, published);
+        assert_eq!(edition.cleared_news.len(), 2);
+        assert!(store.news().is_empty());
+
+        // Unchanged news aren't stored twice
+        store
+            .add_news(news("$third", "@reporter:matrix.local", now))
+            .unwrap();
+        let number = store.publish_edition(editor, "Editor", Vec::new()).unwrap();
+        assert_eq!(store.archive_news(editor, "Editor").unwrap(), Some(number));
+        assert!(store.edition(number).unwrap().cleared_news.is_empty());
+    }
+
+    #[test]
+    fn failed_writes_keep_news() {
+        let stored = news("$stored", "@reporter:matrix.local", Utc::now());
+        let backend = ReadOnlyBackend(HashMap::from([(stored.event_id.clone(), stored)]));
+        let mut store = NewsStore::with_backend(Box::new(backend)).unwrap();
+        let editor = user_id!("@editor:matrix.local");
+
+        let added = news("$added", "@reporter:matrix.local", Utc::now());
+        assert!(store.add_news(added).is_err());
+        assert!(store.news_by_ref("$added").is_none());
+
+        assert!(store.remove_news(event_id!("$stored")).is_err());
+        assert!(store.news_by_ref("$stored").is_some());
+
+        assert!(store.publish_edition(editor, "Editor", Vec::new()).is_err());
+        assert!(store.archive_news(editor, "Editor").is_err());
+        assert_eq!(store.news().len(), 1);
+        assert!(store.editions().is_empty());
     }
 }
@@ -0,0 +1,142 @@
+use matrix_sdk::ruma::{EventId, OwnedEventId};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::news_store::StoreBackend;
+use crate::{Edition, Error, News};
+
+/// Stores all news entries as a single pretty printed JSON file.
+/// Archived editions are stored next to it (`store.json` -> `store.editions.json`).
+///
+/// The file can only be written as a whole, so the backend keeps a copy of the stored entries.
+pub struct JsonBackend {
+    path: PathBuf,
+    editions_path: PathBuf,
+    news_map: RefCell<HashMap<OwnedEventId, News>>,
+}
+
+impl JsonBackend {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let editions_path = path.with_extension("editions.json");
+
+        Self {
+            path,
+            editions_path,
+            news_map: RefCell::default(),
+        }
+    }
+
+    pub fn editions_path(&self) -> &Path {
+        &self.editions_path
+    }
+}
+
+impl StoreBackend for JsonBackend {
+    fn load(&self) -> Result<HashMap<OwnedEventId, News>, Error> {
+        debug!("Trying to read stored news file from path: {:?}", self.path);
+        let news_map: HashMap<OwnedEventId, News> = read_json(&self.path)?.unwrap_or_else(|| {
+            warn!("Unable to open news store file");
+            HashMap::new()
+        });
+
+        *self.news_map.borrow_mut() = news_map.clone();
+        Ok(news_map)
+    }
+
+    fn upsert_news(&self, news: &News) -> Result<(), Error> {
+        let mut news_map = self.news_map.borrow_mut();
+        news_map.insert(news.event_id.clone(), news.clone());
+        write_json(&self.path, &*news_map)
+    }
+
+    fn delete_news(&self, event_id: &EventId) -> Result<(), Error> {
+        let mut news_map = self.news_map.borrow_mut();
+        if news_map.remove(event_id).is_none() {
+            return Ok(());
+        }
+        write_json(&self.path, &*news_map)
+    }
+
+    fn clear_news(&self) -> Result<(), Error> {
+        let mut news_map = self.news_map.borrow_mut();
+        news_map.clear();
+        write_json(&self.path, &*news_map)
+    }
+
+    fn load_editions(&self) -> Result<Vec<Edition>, Error> {
+        debug!(
+            "Trying to read editions archive from path: {:?}",
+            self.editions_path
+        );
+        Ok(read_json(&self.editions_path)?.unwrap_or_default())
+    }
+
+    fn save_editions(&self, editions: &[Edition]) -> Result<(), Error> {
+        write_json(&self.editions_path, editions)
+    }
+}
+
+/// Reads and parses a JSON file, returns `None` if it doesn't exist
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, Error> {
+    match fs::read_to_string(path) {
+        Ok(data) => Ok(Some(serde_json::from_str(&data)?)),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn write_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), Error> {
+    let json = serde_json::to_string_pretty(value)?;
+
+    // Write to a temporary file first, so that a crash can't leave a truncated store behind
+    let mut tmp_path = path.to_path_buf().into_os_string();
+    tmp_path.push(".tmp");
+    fs::write(&tmp_path, json)?;
+    fs::rename(&tmp_path, path)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use matrix_sdk::ruma::{event_id, owned_event_id};
+
+    use super::JsonBackend;
+    use crate::news_store::StoreBackend;
+    use crate::News;
+
+    #[test]
+    fn json_backend_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+
+        let backend = JsonBackend::new(&path);
+        assert!(backend.load().unwrap().is_empty());
+
+        let first = News::test_entry("$first");
+        let second = News::test_entry("$second");
+        backend.upsert_news(&first).unwrap();
+        backend.upsert_news(&second).unwrap();
+
+        // Changing one entry keeps the others
+        first.add_section_name(owned_event_id!("$reaction"), "core".to_owned());
+        backend.upsert_news(&first).unwrap();
+        let stored = JsonBackend::new(&path).load().unwrap();
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored[event_id!("$first")], first);
+
+        backend.delete_news(event_id!("$second")).unwrap();
+        let stored = JsonBackend::new(&path).load().unwrap();
+        assert_eq!(stored.keys().collect::<Vec<_>>(), vec![event_id!("$first")]);
+
+        backend.clear_news().unwrap();
+        assert!(JsonBackend::new(&path).load().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,1085 @@
+use chrono::{DateTime, Utc};
+use matrix_sdk::ruma::{EventId, IdParseError, OwnedEventId, OwnedUserId};
+use rusqlite::{params, Connection, OpenFlags};
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::news_store::{JsonBackend, StoreBackend};
+use crate::{Edition, Error, FollowUp, MediaFile, Mention, News};
+
+/// Schema migrations, the index + 1 is stored as `user_version` once applied
+const MIGRATIONS: &[&str] = &[
+    // 1: Initial schema
+    "CREATE TABLE news (
+        event_id TEXT PRIMARY KEY NOT NULL,
+        reporter_id TEXT NOT NULL,
+        reporter_display_name TEXT NOT NULL,
+        timestamp TEXT NOT NULL,
+        message TEXT NOT NULL
+    );
+    CREATE TABLE reactions (
+        reaction_id TEXT PRIMARY KEY NOT NULL,
+        news_event_id TEXT NOT NULL REFERENCES news(event_id) ON DELETE CASCADE,
+        kind TEXT NOT NULL
+    );
+    CREATE TABLE assignments (
+        reaction_id TEXT PRIMARY KEY NOT NULL REFERENCES reactions(reaction_id) ON DELETE CASCADE,
+        name TEXT NOT NULL
+    );
+    CREATE TABLE media (
+        reaction_id TEXT PRIMARY KEY NOT NULL REFERENCES reactions(reaction_id) ON DELETE CASCADE,
+        file_event_id TEXT NOT NULL,
+        filename TEXT NOT NULL,
+        mxc_uri TEXT NOT NULL
+    );
+    CREATE INDEX reactions_news_event_id ON reactions(news_event_id);",
+    // 2: Editions archive, the news snapshot is stored as JSON
+    "CREATE TABLE editions (
+        number INTEGER PRIMARY KEY NOT NULL,
+        timestamp TEXT NOT NULL,
+        editor_id TEXT NOT NULL,
+        editor_display_name TEXT NOT NULL,
+        rendered TEXT,
+        closed INTEGER NOT NULL,
+        news TEXT NOT NULL
+    );",
+    // 3: Multiple rendered outputs per edition, stored as JSON
+    "ALTER TABLE editions ADD COLUMN outputs TEXT NOT NULL DEFAULT '[]';
+    UPDATE editions SET outputs = json_array(json_object(
+        'name', 'default',
+        'filename', 'rendered.md',
+        'mime_type', 'text/plain; charset=utf-8',
+        'content', rendered
+    )) WHERE rendered IS NOT NULL;",
+    // 4: HTML formatted messages and their Markdown conversion
+    "ALTER TABLE news ADD COLUMN message_html TEXT;
+    ALTER TABLE news ADD COLUMN message_markdown TEXT;",
+    // 5: Users mentioned in news entries
+    "CREATE TABLE mentions (
+        news_event_id TEXT NOT NULL REFERENCES news(event_id) ON DELETE CASCADE,
+        user_id TEXT NOT NULL,
+        display_name TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        PRIMARY KEY (news_event_id, user_id)
+    );",
+    // 6: Media file details
+    "ALTER TABLE media ADD COLUMN caption TEXT;
+    ALTER TABLE media ADD COLUMN mimetype TEXT;
+    ALTER TABLE media ADD COLUMN size INTEGER;
+    ALTER TABLE media ADD COLUMN width INTEGER;
+    ALTER TABLE media ADD COLUMN height INTEGER;
+    ALTER TABLE media ADD COLUMN duration INTEGER;
+    ALTER TABLE media ADD COLUMN blurhash TEXT;
+    ALTER TABLE media ADD COLUMN thumbnail_uri TEXT;",
+    // 7: Follow-up messages posted in the thread of news entries
+    "CREATE TABLE follow_ups (
+        event_id TEXT PRIMARY KEY NOT NULL,
+        news_event_id TEXT NOT NULL REFERENCES news(event_id) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        message TEXT NOT NULL,
+        message_html TEXT,
+        message_markdown TEXT
+    );",
+    // 8: Keys of files sent in encrypted rooms, stored as JSON
+    "ALTER TABLE media ADD COLUMN encryption TEXT;",
+    // 9: Texts set by editors with `!edit`, stored as JSON
+    "ALTER TABLE news ADD COLUMN editor_edit TEXT;",
+    // 10: Manual ordering of news entries
+    "ALTER TABLE news ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE news ADD COLUMN position INTEGER;",
+    // 11: Approvals of the review workflow
+    "CREATE TABLE approvals (
+        reaction_id TEXT PRIMARY KEY NOT NULL REFERENCES reactions(reaction_id) ON DELETE CASCADE,
+        editor_id TEXT NOT NULL
+    );",
+    // 12: News which changed after publishing an edition, stored as JSON
+    "ALTER TABLE editions ADD COLUMN cleared_news TEXT NOT NULL DEFAULT '[]';",
+    // 13: Reactions whose assignment / approval got removed with an admin command
+    "CREATE TABLE dismissed_reactions (
+        reaction_id TEXT PRIMARY KEY NOT NULL,
+        news_event_id TEXT NOT NULL REFERENCES news(event_id) ON DELETE CASCADE
+    );",
+    // 14: When images / videos were sent, to order them
+    "ALTER TABLE media ADD COLUMN timestamp TEXT;",
+    // 15: Key reactions by their kind as well, the same event can e.g. assign and approve an entry
+    "ALTER TABLE assignments RENAME TO old_assignments;
+    ALTER TABLE media RENAME TO old_media;
+    ALTER TABLE approvals RENAME TO old_approvals;
+    ALTER TABLE reactions RENAME TO old_reactions;
+    CREATE TABLE reactions (
+        reaction_id TEXT NOT NULL,
+        news_event_id TEXT NOT NULL REFERENCES news(event_id) ON DELETE CASCADE,
+        kind TEXT NOT NULL,
+        PRIMARY KEY (reaction_id, kind)
+    );
+    CREATE TABLE assignments (
+        reaction_id TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        name TEXT NOT NULL,
+        PRIMARY KEY (reaction_id, kind),
+        FOREIGN KEY (reaction_id, kind) REFERENCES reactions(reaction_id, kind) ON DELETE CASCADE
+    );
+    CREATE TABLE media (
+        reaction_id TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        file_event_id TEXT NOT NULL,
+        filename TEXT NOT NULL,
+        mxc_uri TEXT NOT NULL,
+        caption TEXT,
+        mimetype TEXT,
+        size INTEGER,
+        width INTEGER,
+        height INTEGER,
+        duration INTEGER,
+        blurhash TEXT,
+        thumbnail_uri TEXT,
+        encryption TEXT,
+        timestamp TEXT,
+        PRIMARY KEY (reaction_id, kind),
+        FOREIGN KEY (reaction_id, kind) REFERENCES reactions(reaction_id, kind) ON DELETE CASCADE
+    );
+    CREATE TABLE approvals (
+        reaction_id TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        editor_id TEXT NOT NULL,
+        PRIMARY KEY (reaction_id, kind),
+        FOREIGN KEY (reaction_id, kind) REFERENCES reactions(reaction_id, kind) ON DELETE CASCADE
+    );
+    INSERT INTO reactions (reaction_id, news_event_id, kind)
+        SELECT reaction_id, news_event_id, kind FROM old_reactions;
+    INSERT INTO assignments (reaction_id, kind, name)
+        SELECT a.reaction_id, r.kind, a.name
+        FROM old_assignments a JOIN old_reactions r ON r.reaction_id = a.reaction_id;
+    INSERT INTO media (reaction_id, kind, file_event_id, filename, mxc_uri, caption, mimetype,
+            size, width, height, duration, blurhash, thumbnail_uri, encryption, timestamp)
+        SELECT m.reaction_id, r.kind, m.file_event_id, m.filename, m.mxc_uri, m.caption,
+            m.mimetype, m.size, m.width, m.height, m.duration, m.blurhash, m.thumbnail_uri,
+            m.encryption, m.timestamp
+        FROM old_media m JOIN old_reactions r ON r.reaction_id = m.reaction_id;
+    INSERT INTO approvals (reaction_id, kind, editor_id)
+        SELECT a.reaction_id, r.kind, a.editor_id
+        FROM old_approvals a JOIN old_reactions r ON r.reaction_id = a.reaction_id;
+    DROP TABLE old_assignments;
+    DROP TABLE old_media;
+    DROP TABLE old_approvals;
+    DROP TABLE old_reactions;
+    CREATE INDEX reactions_news_event_id ON reactions(news_event_id);",
+];
+
+/// Stores news entries in a SQLite database
+pub struct SqliteBackend {
+    connection: Connection,
+}
+
+impl SqliteBackend {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        debug!("Opening news store database: {:?}", path.as_ref());
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Opens the database without migrating it, so it can be read while the bot is using it
+    pub fn open_read_only(path: impl AsRef<Path>) -> Result<Self, Error> {
+        debug!("Opening news store database read-only: {:?}", path.as_ref());
+        let flags = OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+        let connection = Connection::open_with_flags(path, flags)?;
+
+        let version: usize =
+            connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version > MIGRATIONS.len() {
+            return Err(Error::UnsupportedStoreVersion(version));
+        } else if version < MIGRATIONS.len() {
+            return Err(Error::OutdatedStoreVersion(version));
+        }
+
+        Ok(Self { connection })
+    }
+
+    fn with_connection(connection: Connection) -> Result<Self, Error> {
+        connection.pragma_update(None, "foreign_keys", true)?;
+        connection.pragma_update(None, "journal_mode", "WAL")?;
+
+        let backend = Self { connection };
+        backend.migrate()?;
+        Ok(backend)
+    }
+
+    /// Applies all schema migrations which haven't been applied yet
+    fn migrate(&self) -> Result<(), Error> {
+        let version: usize = self
+            .connection
+            .pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+        if version > MIGRATIONS.len() {
+            return Err(Error::UnsupportedStoreVersion(version));
+        }
+
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            info!("Migrating news store to schema version {}", index + 1);
+
+            let transaction = self.connection.unchecked_transaction()?;
+            transaction.execute_batch(migration)?;
+            transaction.pragma_update(None, "user_version", index + 1)?;
+            transaction.commit()?;
+        }
+
+        Ok(())
+    }
+
+    /// Imports the news entries and editions of a `store.json` file, if the database is still empty.
+    /// The JSON files get renamed afterwards, so they're not getting imported again.
+    pub fn import_json(&self, path: impl Into<PathBuf>) -> Result<(), Error> {
+        let path = path.into();
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let count: usize = self.connection.query_row(
+            "SELECT (SELECT COUNT(*) FROM news) + (SELECT COUNT(*) FROM editions)",
+            [],
+            |row| row.get(0),
+        )?;
+        if count != 0 {
+            warn!(
+                "Not importing {:?}, the news store database isn’t empty",
+                path
+            );
+            return Ok(());
+        }
+
+        let json_backend = JsonBackend::new(path.clone());
+        let news_map = json_backend.load()?;
+        let editions = json_backend.load_editions()?;
+        for news in news_map.values() {
+            self.upsert_news(news)?;
+        }
+        self.save_editions(&editions)?;
+
+        mark_imported(&path)?;
+        if json_backend.editions_path().exists() {
+            mark_imported(json_backend.editions_path())?;
+        }
+
+        info!(
+            "Imported {} news entries and {} editions from {:?}",
+            news_map.len(),
+            editions.len(),
+            path,
+        );
+        Ok(())
+    }
+}
+
+impl StoreBackend for SqliteBackend {
+    fn load(&self) -> Result<HashMap<OwnedEventId, News>, Error> {
+        let mut news_map = HashMap::new();
+
+        let mut statement = self.connection.prepare(
+            "SELECT event_id, reporter_id, reporter_display_name, timestamp, message,
+                message_html, message_markdown, editor_edit, pinned, position
+            FROM news",
+        )?;
+        let rows = statement.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, DateTime<Utc>>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, Option<String>>(5)?,
+                row.get::<_, Option<String>>(6)?,
+                row.get::<_, Option<String>>(7)?,
+                row.get::<_, bool>(8)?,
+                row.get::<_, Option<u32>>(9)?,
+            ))
+        })?;
+
+        for row in rows {
+            let (
+                event_id,
+                reporter_id,
+                reporter_display_name,
+                timestamp,
+                message,
+                message_html,
+                message_markdown,
+                editor_edit,
+                pinned,
+                position,
+            ) = row?;
+            let event_id = OwnedEventId::try_from(event_id)?;
+            let reporter_id = OwnedUserId::try_from(reporter_id)?;
+
+            let news = News::from_parts(
+                event_id.clone(),
+                reporter_id,
+                reporter_display_name,
+                timestamp,
+                message,
+            );
+            news.set_formatted_message(message_html, message_markdown);
+            news.set_editor_edit(
+                editor_edit
+                    .map(|json| serde_json::from_str(&json))
+                    .transpose()?,
+            );
+            news.set_pinned(pinned);
+            news.set_position(position);
+            news_map.insert(event_id, news);
+        }
+
+        let mut statement = self.connection.prepare(
+            "SELECT r.news_event_id, r.reaction_id, r.kind, a.name
+            FROM reactions r JOIN assignments a ON a.reaction_id = r.reaction_id AND a.kind = r.kind",
+        )?;
+        let rows = statement.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?;
+
+        for row in rows {
+            let (news_event_id, reaction_id, kind, name) = row?;
+            let Some(news) = news_map.get(&OwnedEventId::try_from(news_event_id)?) else {
+                continue;
+            };
+            let reaction_id = OwnedEventId::try_from(reaction_id)?;
+
+            match kind.as_str() {
+                "section" => news.add_section_name(reaction_id, name),
+                "project" => news.add_project_name(reaction_id, name),
+                _ => warn!("Unknown assignment kind “{}” in news store", kind),
+            }
+        }
+
+        let mut statement = self.connection.prepare(
+            "SELECT r.news_event_id, r.reaction_id, a.editor_id
+            FROM reactions r JOIN approvals a ON a.reaction_id = r.reaction_id AND a.kind = r.kind",
+        )?;
+        let rows = statement.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?;
+
+        for row in rows {
+            let (news_event_id, reaction_id, editor_id) = row?;
+            if let Some(news) = news_map.get(&OwnedEventId::try_from(news_event_id)?) {
+                news.add_approval(
+                    OwnedEventId::try_from(reaction_id)?,
+                    OwnedUserId::try_from(editor_id)?,
+                );
+            }
+        }
+
+        let mut statement = self.connection.prepare(
+            "SELECT news_event_id, reaction_id FROM dismissed_reactions ORDER BY reaction_id",
+        )?;
+        let rows = statement.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        for row in rows {
+            let (news_event_id, reaction_id) = row?;
+            if let Some(news) = news_map.get(&OwnedEventId::try_from(news_event_id)?) {
+                news.add_dismissed_reaction(OwnedEventId::try_from(reaction_id)?);
+            }
+        }
+
+        let mut statement = self.connection.prepare(
+            "SELECT r.news_event_id, r.reaction_id, r.kind, m.file_event_id, m.filename, m.mxc_uri,
+                m.caption, m.mimetype, m.size, m.width, m.height, m.duration, m.blurhash,
+                m.thumbnail_uri, m.encryption, m.timestamp
+            FROM reactions r JOIN media m ON m.reaction_id = r.reaction_id AND m.kind = r.kind",
+        )?;
+        let rows = statement.query_map([], |row| {
+            let file = MediaFile {
+                event_id: row.get::<_, String>(3)?.try_into().map_err(invalid_id)?,
+                timestamp: row.get(15)?,
+                filename: row.get(4)?,
+                mxc_uri: row.get::<_, String>(5)?.into(),
+                caption: row.get(6)?,
+                mimetype: row.get(7)?,
+                size: row.get(8)?,
+                width: row.get(9)?,
+                height: row.get(10)?,
+                duration: row.get(11)?,
+                blurhash: row.get(12)?,
+                thumbnail_uri: row.get::<_, Option<String>>(13)?.map(Into::into),
+                encryption: row
+                    .get::<_, Option<String>>(14)?
+                    .map(|json| serde_json::from_str(&json))
+                    .transpose()
+                    .map_err(|err| {
+                        rusqlite::Error::FromSqlConversionFailure(
+                            14,
+                            rusqlite::types::Type::Text,
+                            Box::new(err),
+                        )
+                    })?,
+            };
+
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                file,
+            ))
+        })?;
+
+        for row in rows {
+            let (news_event_id, reaction_id, kind, file) = row?;
+            let Some(news) = news_map.get(&OwnedEventId::try_from(news_event_id)?) else {
+                continue;
+            };
+            let reaction_id = OwnedEventId::try_from(reaction_id)?;
+
+            match kind.as_str() {
+                "image" => news.add_image(reaction_id, file),
+                "video" => news.add_video(reaction_id, file),
+                _ => warn!("Unknown media kind “{}” in news store", kind),
+            }
+        }
+
+        let mut statement = self.connection.prepare(
+            "SELECT news_event_id, user_id, display_name FROM mentions ORDER BY position",
+        )?;
+        let rows = statement.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?;
+
+        for row in rows {
+            let (news_event_id, user_id, display_name) = row?;
+            let Some(news) = news_map.get(&OwnedEventId::try_from(news_event_id)?) else {
+                continue;
+            };
+
+            let mut mentions = news.mentions();
+            mentions.push(Mention {
+                user_id: OwnedUserId::try_from(user_id)?,
+                display_name,
+            });
+            news.set_mentions(mentions);
+        }
+
+        let mut statement = self.connection.prepare(
+            "SELECT news_event_id, event_id, message, message_html, message_markdown
+            FROM follow_ups ORDER BY position",
+        )?;
+        let rows = statement.query_map([], |row| {
+            let follow_up = FollowUp {
+                event_id: row.get::<_, String>(1)?.try_into().map_err(invalid_id)?,
+                message: row.get(2)?,
+                message_html: row.get(3)?,
+                message_markdown: row.get(4)?,
+            };
+            Ok((row.get::<_, String>(0)?, follow_up))
+        })?;
+
+        for row in rows {
+            let (news_event_id, follow_up) = row?;
+            if let Some(news) = news_map.get(&OwnedEventId::try_from(news_event_id)?) {
+                news.add_follow_up(follow_up);
+            }
+        }
+
+        Ok(news_map)
+    }
+
+    fn upsert_news(&self, news: &News) -> Result<(), Error> {
+        let transaction = self.connection.unchecked_transaction()?;
+        let event_id = news.event_id.as_str();
+
+        transaction.execute(
+            "INSERT INTO news (event_id, reporter_id, reporter_display_name, timestamp, message,
+                message_html, message_markdown, editor_edit, pinned, position)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+            ON CONFLICT(event_id) DO UPDATE SET
+                reporter_id = excluded.reporter_id,
+                reporter_display_name = excluded.reporter_display_name,
+                timestamp = excluded.timestamp,
+                message = excluded.message,
+                message_html = excluded.message_html,
+                message_markdown = excluded.message_markdown,
+                editor_edit = excluded.editor_edit,
+                pinned = excluded.pinned,
+                position = excluded.position",
+            params![
+                event_id,
+                news.reporter_id.as_str(),
+                news.reporter_display_name,
+                news.timestamp,
+                news.original_message(),
+                news.original_message_html(),
+                news.original_message_markdown(),
+                news.editor_edit()
+                    .as_ref()
+                    .map(serde_json::to_string)
+                    .transpose()?,
+                news.is_pinned(),
+                news.position(),
+            ],
+        )?;
+
+        // Reactions are cheap to rebuild, so simply replace them
+        transaction.execute("DELETE FROM reactions WHERE news_event_id = ?1", [event_id])?;
+
+        let assignments = news
+            .section_reactions()
+            .into_iter()
+            .map(|(reaction_id, name)| (reaction_id, "section", name))
+            .chain(
+                news.project_reactions()
+                    .into_iter()
+                    .map(|(reaction_id, name)| (reaction_id, "project", name)),
+            );
+        for (reaction_id, kind, name) in assignments {
+            insert_reaction(&transaction, event_id, reaction_id.as_str(), kind)?;
+            transaction.execute(
+                "INSERT INTO assignments (reaction_id, kind, name) VALUES (?1, ?2, ?3)",
+                params![reaction_id.as_str(), kind, name],
+            )?;
+        }
+
+        for (reaction_id, editor_id) in news.approval_reactions() {
+            insert_reaction(&transaction, event_id, reaction_id.as_str(), "review")?;
+            transaction.execute(
+                "INSERT INTO approvals (reaction_id, kind, editor_id) VALUES (?1, 'review', ?2)",
+                params![reaction_id.as_str(), editor_id.as_str()],
+            )?;
+        }
+
+        transaction.execute(
+            "DELETE FROM dismissed_reactions WHERE news_event_id = ?1",
+            [event_id],
+        )?;
+        for reaction_id in news.dismissed_reactions() {
+            transaction.execute(
+                "INSERT OR IGNORE INTO dismissed_reactions (reaction_id, news_event_id)
+                VALUES (?1, ?2)",
+                params![reaction_id.as_str(), event_id],
+            )?;
+        }
+
+        let media = news
+            .image_reactions()
+            .into_iter()
+            .map(|(reaction_id, file)| (reaction_id, "image", file))
+            .chain(
+                news.video_reactions()
+                    .into_iter()
+                    .map(|(reaction_id, file)| (reaction_id, "video", file)),
+            );
+        for (reaction_id, kind, file) in media {
+            insert_reaction(&transaction, event_id, reaction_id.as_str(), kind)?;
+            transaction.execute(
+                "INSERT INTO media (reaction_id, kind, file_event_id, filename, mxc_uri, caption,
+                    mimetype, size, width, height, duration, blurhash, thumbnail_uri, encryption,
+                    timestamp)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+                params![
+                    reaction_id.as_str(),
+                    kind,
+                    file.event_id.as_str(),
+                    file.filename,
+                    file.mxc_uri.as_str(),
+                    file.caption,
+                    file.mimetype,
+                    file.size,
+                    file.width,
+                    file.height,
+                    file.duration,
+                    file.blurhash,
+                    file.thumbnail_uri.as_ref().map(|uri| uri.as_str()),
+                    file.encryption
+                        .as_ref()
+                        .map(serde_json::to_string)
+                        .transpose()?,
+                    file.timestamp,
+                ],
+            )?;
+        }
+
+        transaction.execute("DELETE FROM mentions WHERE news_event_id = ?1", [event_id])?;
+        for (position, mention) in news.mentions().into_iter().enumerate() {
+            transaction.execute(
+                "INSERT INTO mentions (news_event_id, user_id, display_name, position)
+                VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    event_id,
+                    mention.user_id.as_str(),
+                    mention.display_name,
+                    position
+                ],
+            )?;
+        }
+
+        transaction.execute(
+            "DELETE FROM follow_ups WHERE news_event_id = ?1",
+            [event_id],
+        )?;
+        for (position, follow_up) in news.follow_ups().into_iter().enumerate() {
+            transaction.execute(
+                "INSERT INTO follow_ups (event_id, news_event_id, position, message,
+                    message_html, message_markdown)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    follow_up.event_id.as_str(),
+                    event_id,
+                    position,
+                    follow_up.message,
+                    follow_up.message_html,
+                    follow_up.message_markdown,
+                ],
+            )?;
+        }
+
+        transaction.commit()?;
+        Ok(())
+    }
+
+    fn delete_news(&self, event_id: &EventId) -> Result<(), Error> {
+        // Reactions, mentions and follow-ups are removed by the foreign key constraints
+        self.connection
+            .execute("DELETE FROM news WHERE event_id = ?1", [event_id.as_str()])?;
+        Ok(())
+    }
+
+    fn clear_news(&self) -> Result<(), Error> {
+        self.connection.execute("DELETE FROM news", [])?;
+        Ok(())
+    }
+
+    fn load_editions(&self) -> Result<Vec<Edition>, Error> {
+        let mut statement = self.connection.prepare(
+            "SELECT number, timestamp, editor_id, editor_display_name, outputs, closed, news,
+                cleared_news
+            FROM editions ORDER BY number",
+        )?;
+        let rows = statement.query_map([], |row| {
+            Ok((
+                row.get::<_, u32>(0)?,
+                row.get::<_, DateTime<Utc>>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, bool>(5)?,
+                row.get::<_, String>(6)?,
+                row.get::<_, String>(7)?,
+            ))
+        })?;
+
+        let mut editions = Vec::new();
+        for row in rows {
+            let (
+                number,
+                timestamp,
+                editor_id,
+                editor_display_name,
+                outputs,
+                closed,
+                news,
+                cleared_news,
+            ) = row?;
+
+            editions.push(Edition {
+                number,
+                timestamp,
+                editor_id: OwnedUserId::try_from(editor_id)?,
+                editor_display_name,
+                outputs: serde_json::from_str(&outputs)?,
+                closed,
+                news: serde_json::from_str(&news)?,
+                cleared_news: serde_json::from_str(&cleared_news)?,
+            });
+        }
+
+        Ok(editions)
+    }
+
+    fn save_editions(&self, editions: &[Edition]) -> Result<(), Error> {
+        let transaction = self.connection.unchecked_transaction()?;
+
+        for edition in editions {
+            transaction.execute(
+                "INSERT OR REPLACE INTO editions
+                (number, timestamp, editor_id, editor_display_name, outputs, closed, news,
+                    cleared_news)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    edition.number,
+                    edition.timestamp,
+                    edition.editor_id.as_str(),
+                    edition.editor_display_name,
+                    serde_json::to_string(&edition.outputs)?,
+                    edition.closed,
+                    serde_json::to_string(&edition.news)?,
+                    serde_json::to_string(&edition.cleared_news)?,
+                ],
+            )?;
+        }
+
+        transaction.commit()?;
+        Ok(())
+    }
+}
+
+/// Renames an imported JSON file (`store.json` -> `store.json.imported`)
+fn mark_imported(path: &Path) -> Result<(), Error> {
+    let mut imported_path = path.to_path_buf().into_os_string();
+    imported_path.push(".imported");
+    fs::rename(path, &imported_path)?;
+
+    info!("Renamed imported file {:?} to {:?}", path, imported_path);
+    Ok(())
+}
+
+/// Maps invalid ids in a row to a conversion error, so they can be returned from `query_map`
+fn invalid_id(err: IdParseError) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(err))
+}
+
+fn insert_reaction(
+    connection: &Connection,
+    news_event_id: &str,
+    reaction_id: &str,
+    kind: &str,
+) -> Result<(), Error> {
+    connection.execute(
+        "INSERT INTO reactions (reaction_id, news_event_id, kind) VALUES (?1, ?2, ?3)",
+        params![reaction_id, news_event_id, kind],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use matrix_sdk::ruma::{event_id, owned_event_id, owned_mxc_uri, owned_user_id};
+    use rusqlite::Connection;
+
+    use assert_matches2::assert_matches;
+
+    use std::collections::HashMap;
+
+    use super::{SqliteBackend, MIGRATIONS};
+    use crate::news_store::StoreBackend;
+    use crate::{
+        Edition, EditorEdit, Error, FollowUp, MediaFile, Mention, News, RenderedOutput, ReviewState,
+    };
+
+    #[test]
+    fn sqlite_backend_migrations() {
+        let backend =
+            SqliteBackend::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+        let version: usize = backend
+            .connection
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len());
+
+        // Migrating again is a no-op
+        backend.migrate().unwrap();
+    }
+
+    #[test]
+    fn sqlite_backend_read_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.db");
+
+        // Not migrated yet
+        Connection::open(&path).unwrap();
+        assert_matches!(
+            SqliteBackend::open_read_only(&path).err(),
+            Some(Error::OutdatedStoreVersion(0))
+        );
+
+        let backend = SqliteBackend::open(&path).unwrap();
+        backend.upsert_news(&News::test_entry("$first")).unwrap();
+
+        let read_only = SqliteBackend::open_read_only(&path).unwrap();
+        assert_eq!(read_only.load().unwrap().len(), 1);
+        assert!(read_only.upsert_news(&News::test_entry("$second")).is_err());
+    }
+
+    #[test]
+    fn sqlite_backend_roundtrip() {
+        let backend =
+            SqliteBackend::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+
+        let first = News::test_entry("$first");
+        first.set_pinned(true);
+        first.set_position(Some(2));
+        first.add_section_name(owned_event_id!("$section_reaction"), "core".to_owned());
+        first.add_project_name(owned_event_id!("$project_reaction"), "hebbot".to_owned());
+        first.add_approval(
+            owned_event_id!("$review_reaction"),
+            owned_user_id!("@editor:matrix.local"),
+        );
+        let mut image = MediaFile::new(
+            owned_event_id!("$image"),
+            "image.png".to_owned(),
+            owned_mxc_uri!("mxc://matrix.local/01234"),
+        );
+        image.timestamp = Some(Utc::now());
+        image.caption = Some("A screenshot".to_owned());
+        image.width = Some(1920);
+        image.height = Some(1080);
+        first.add_image(owned_event_id!("$image_reaction"), image);
+        first.add_video(
+            owned_event_id!("$video_reaction"),
+            MediaFile::new(
+                owned_event_id!("$video"),
+                "video.webm".to_owned(),
+                owned_mxc_uri!("mxc://matrix.local/56789"),
+            ),
+        );
+        let second = News::test_entry("$second");
+        second.set_message_html(Some("This is <b>my</b> entry".to_owned()));
+        second.set_mentions(vec![
+            Mention {
+                user_id: owned_user_id!("@bob:matrix.local"),
+                display_name: "Bob".to_owned(),
+            },
+            Mention {
+                user_id: owned_user_id!("@alice:matrix.local"),
+                display_name: "Alice".to_owned(),
+            },
+        ]);
+
+        let mut editor_edit = EditorEdit::new(
+            owned_user_id!("@editor:matrix.local"),
+            "This is the entry".to_owned(),
+        );
+        second.set_editor_edit(Some(editor_edit.clone()));
+        assert!(second.mark_edit_conflict());
+        editor_edit.conflict = true;
+        assert_eq!(second.editor_edit(), Some(editor_edit));
+        assert_eq!(second.edited_message(), "This is the entry");
+
+        backend.upsert_news(&first).unwrap();
+        backend.upsert_news(&second).unwrap();
+        let mut news_map = HashMap::new();
+        news_map.insert(first.event_id.clone(), first);
+        news_map.insert(second.event_id.clone(), second);
+        assert_eq!(backend.load().unwrap(), news_map);
+
+        // Removed news and reactions are removed from the database too
+        backend.delete_news(event_id!("$second")).unwrap();
+        news_map.remove(event_id!("$second"));
+        let first = &news_map[event_id!("$first")];
+        first.remove_reaction_id(event_id!("$section_reaction"));
+        backend.upsert_news(first).unwrap();
+        assert_eq!(backend.load().unwrap(), news_map);
+
+        // Reactions removed with `!unassign` are remembered
+        first.remove_project_name(None);
+        backend.upsert_news(first).unwrap();
+        assert_eq!(backend.load().unwrap(), news_map);
+        assert_eq!(
+            first.dismissed_reactions(),
+            vec![owned_event_id!("$project_reaction")]
+        );
+
+        backend.clear_news().unwrap();
+        assert!(backend.load().unwrap().is_empty());
+    }
+
+    #[test]
+    fn sqlite_backend_follow_ups() {
+        let backend =
+            SqliteBackend::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+
+        let news = News::test_entry("$news");
+        news.set_message_html(Some("This is <b>my</b> entry".to_owned()));
+        news.add_follow_up(FollowUp::new(
+            owned_event_id!("$follow_up"),
+            "More details".to_owned(),
+            Some("More <em>details</em>".to_owned()),
+        ));
+        news.add_follow_up(FollowUp::new(
+            owned_event_id!("$plain_follow_up"),
+            "Even <more>".to_owned(),
+            None,
+        ));
+        backend.upsert_news(&news).unwrap();
+
+        let stored = &backend.load().unwrap()[event_id!("$news")];
+        assert_eq!(stored.follow_ups(), news.follow_ups());
+        assert_eq!(
+            stored.message(),
+            "This is my entry\n\nMore details\n\nEven <more>"
+        );
+        assert_eq!(
+            stored.message_html().unwrap(),
+            "<div>This is <b>my</b> entry</div>\n<div>More <em>details</em></div>\n<p>Even &lt;more&gt;</p>"
+        );
+        assert_eq!(
+            stored.message_markdown(),
+            "This is **my** entry\n\nMore *details*\n\nEven <more>"
+        );
+
+        // Edited follow-ups keep their position, deleted ones are removed
+        news.add_follow_up(FollowUp::new(
+            owned_event_id!("$follow_up"),
+            "Fewer details".to_owned(),
+            None,
+        ));
+        news.remove_follow_up(event_id!("$plain_follow_up"));
+        news.add_follow_up(FollowUp::new(
+            owned_event_id!("$last_follow_up"),
+            "The end".to_owned(),
+            None,
+        ));
+        backend.upsert_news(&news).unwrap();
+
+        let stored = &backend.load().unwrap()[event_id!("$news")];
+        assert_eq!(
+            stored.message(),
+            "This is my entry\n\nFewer details\n\nThe end"
+        );
+        assert!(stored.relates_to_follow_up_id(event_id!("$last_follow_up")));
+        assert!(!stored.relates_to_follow_up_id(event_id!("$plain_follow_up")));
+    }
+
+    #[test]
+    fn sqlite_backend_approvals() {
+        let backend =
+            SqliteBackend::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+        let editor = owned_user_id!("@editor:matrix.local");
+        let other_editor = owned_user_id!("@other:matrix.local");
+
+        let news = News::test_entry("$news");
+        news.add_section_name(owned_event_id!("$section_reaction"), "core".to_owned());
+        news.add_approval(owned_event_id!("$review_reaction"), editor.clone());
+        news.add_approval(owned_event_id!("$approve_command"), editor.clone());
+        news.add_approval(owned_event_id!("$other_reaction"), other_editor.clone());
+        backend.upsert_news(&news).unwrap();
+
+        let stored = &backend.load().unwrap()[event_id!("$news")];
+        assert_eq!(stored.approval_reactions(), news.approval_reactions());
+        assert_eq!(
+            stored.approvers(),
+            vec![editor.clone(), other_editor.clone()]
+        );
+        assert_eq!(stored.review_state(2), ReviewState::Reviewed);
+
+        // Withdrawn approvals are removed, the other reactions are kept
+        assert!(news.remove_approvals_by(&editor));
+        backend.upsert_news(&news).unwrap();
+
+        let stored = &backend.load().unwrap()[event_id!("$news")];
+        assert_eq!(stored.approvers(), vec![other_editor]);
+        assert_eq!(
+            stored.review_state(2),
+            ReviewState::Assigned {
+                approvals: 1,
+                required: 2
+            }
+        );
+        assert_eq!(stored.section_names(), vec!["core".to_owned()]);
+
+        // Withdrawn approvals aren't restored by resyncing
+        assert_eq!(stored.dismissed_reactions(), news.dismissed_reactions());
+        assert!(stored.relates_to_reaction_id(event_id!("$review_reaction")));
+        assert!(stored.relates_to_reaction_id(event_id!("$approve_command")));
+    }
+
+    #[test]
+    fn sqlite_backend_shared_reaction_id() {
+        let backend =
+            SqliteBackend::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+
+        // The same command event assigns and approves the entry
+        let news = News::test_entry("$news");
+        news.add_section_name(owned_event_id!("$command"), "core".to_owned());
+        news.add_project_name(owned_event_id!("$command"), "hebbot".to_owned());
+        news.add_approval(
+            owned_event_id!("$command"),
+            owned_user_id!("@editor:matrix.local"),
+        );
+        backend.upsert_news(&news).unwrap();
+
+        let stored = &backend.load().unwrap()[event_id!("$news")];
+        assert_eq!(stored.section_names(), vec!["core".to_owned()]);
+        assert_eq!(stored.project_names(), vec!["hebbot".to_owned()]);
+        assert_eq!(stored.approval_reactions(), news.approval_reactions());
+    }
+
+    #[test]
+    fn sqlite_backend_reaction_kinds_migration() {
+        let connection = Connection::open_in_memory().unwrap();
+        connection
+            .pragma_update(None, "foreign_keys", true)
+            .unwrap();
+        for migration in &MIGRATIONS[..14] {
+            connection.execute_batch(migration).unwrap();
+        }
+        connection.pragma_update(None, "user_version", 14).unwrap();
+        connection
+            .execute_batch(
+                "INSERT INTO news (event_id, reporter_id, reporter_display_name, timestamp, message)
+                VALUES ('$news', '@reporter:matrix.local', 'Reporter', '2024-01-01T00:00:00Z',
+                    'This is my entry');
+                INSERT INTO reactions (reaction_id, news_event_id, kind) VALUES
+                    ('$section_reaction', '$news', 'section'),
+                    ('$review_reaction', '$news', 'review'),
+                    ('$image_reaction', '$news', 'image');
+                INSERT INTO assignments (reaction_id, name) VALUES ('$section_reaction', 'core');
+                INSERT INTO approvals (reaction_id, editor_id)
+                    VALUES ('$review_reaction', '@editor:matrix.local');
+                INSERT INTO media (reaction_id, file_event_id, filename, mxc_uri)
+                    VALUES ('$image_reaction', '$image', 'image.png', 'mxc://matrix.local/01234');",
+            )
+            .unwrap();
+
+        let backend = SqliteBackend::with_connection(connection).unwrap();
+        let stored = &backend.load().unwrap()[event_id!("$news")];
+        assert_eq!(stored.section_names(), vec!["core".to_owned()]);
+        assert_eq!(
+            stored.approvers(),
+            vec![owned_user_id!("@editor:matrix.local")]
+        );
+        assert_eq!(stored.images().len(), 1);
+    }
+
+    #[test]
+    fn sqlite_backend_editions_roundtrip() {
+        let backend =
+            SqliteBackend::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+
+        let mut edition = Edition {
+            number: 1,
+            timestamp: Utc::now(),
+            editor_id: owned_user_id!("@editor:matrix.local"),
+            editor_display_name: "Editor".to_owned(),
+            outputs: vec![RenderedOutput {
+                name: "blog".to_owned(),
+                filename: "post.md".to_owned(),
+                mime_type: "text/markdown".to_owned(),
+                content: "# This Week in X".to_owned(),
+            }],
+            closed: false,
+            news: vec![News::test_entry("$first"), News::test_entry("$second")],
+            cleared_news: Vec::new(),
+        };
+        backend.save_editions(&[edition.clone()]).unwrap();
+        assert_eq!(backend.load_editions().unwrap(), vec![edition.clone()]);
+
+        // Closing an edition updates the stored one
+        edition.closed = true;
+        edition.cleared_news = vec![News::test_entry("$third")];
+        backend.save_editions(&[edition.clone()]).unwrap();
+        assert_eq!(backend.load_editions().unwrap(), vec![edition]);
+    }
+}
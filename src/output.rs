@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+/// A rendered file generated from the stored news (e.g. a blog post, or a forum post)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct Output {
+    pub name: String,
+    pub template: String,
+    pub filename: String,
+    #[serde(default = "default_mime_type")]
+    pub mime_type: String,
+}
+
+impl Output {
+    pub fn html_details(&self) -> String {
+        format!(
+            "<b>Output Details</b><br>\
+            <b>Name</b>: {} <br>\
+            <b>Template</b>: {} <br>\
+            <b>Filename</b>: {} <br>\
+            <b>MIME Type</b>: {} <br>",
+            self.name, self.template, self.filename, self.mime_type,
+        )
+    }
+}
+
+/// The content generated for an `Output`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RenderedOutput {
+    pub name: String,
+    pub filename: String,
+    pub mime_type: String,
+    pub content: String,
+}
+
+pub fn default_mime_type() -> String {
+    mime::TEXT_PLAIN_UTF_8.to_string()
+}
@@ -1,3 +1,4 @@
+use matrix_sdk::ruma::{OwnedUserId, UserId};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash, Default)]
@@ -8,9 +9,16 @@ pub struct Project {
     pub description: String,
     pub website: String,
     pub default_section: String,
+    /// Users who can assign news entries to this project, without being editors
+    #[serde(default)]
+    pub maintainers: Vec<OwnedUserId>,
 }
 
 impl Project {
+    pub fn is_maintained_by(&self, user_id: &UserId) -> bool {
+        self.maintainers.iter().any(|id| id == user_id)
+    }
+
     pub fn html_details(&self) -> String {
         format!(
             "<b>Project Details</b><br>\
@@ -18,8 +26,19 @@ impl Project {
             <b>Name</b>: {} ({}) <br>\
             <b>Description</b>: {} <br>\
             <b>Website</b>: {} <br>\
-            <b>Default Section</b>: {} <br>",
-            self.emoji, self.title, self.name, self.description, self.website, self.default_section,
+            <b>Default Section</b>: {} <br>\
+            <b>Maintainers</b>: {} <br>",
+            self.emoji,
+            self.title,
+            self.name,
+            self.description,
+            self.website,
+            self.default_section,
+            self.maintainers
+                .iter()
+                .map(|id| id.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
         )
     }
 }
@@ -8,6 +8,38 @@ pub enum ReactionType {
     Project(Option<Project>),
     None,
     Notice,
+    Review,
+}
+
+/// Whether a reaction gets applied, see [`ReactionType::permission`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReactionPermission {
+    Allowed,
+    Denied,
+    /// Project maintainers can only use the emoji of their projects
+    MaintainerOnly,
+}
+
+impl ReactionType {
+    /// Whether the reaction of a user gets applied. Users are `permitted` if they are editors,
+    /// have a role allowing the reaction or maintain the project of the emoji.
+    /// Notice reactions of others than the reporter are checked when processing them.
+    pub fn permission(
+        &self,
+        permitted: bool,
+        maintainer_only: bool,
+        restrict_notice: bool,
+    ) -> ReactionPermission {
+        match self {
+            _ if permitted => ReactionPermission::Allowed,
+            ReactionType::Notice | ReactionType::None => ReactionPermission::Allowed,
+            _ if restrict_notice && maintainer_only => ReactionPermission::MaintainerOnly,
+            _ if restrict_notice => ReactionPermission::Denied,
+            // Only editors can sign off news entries
+            ReactionType::Review => ReactionPermission::Denied,
+            _ => ReactionPermission::Allowed,
+        }
+    }
 }
 
 impl fmt::Display for ReactionType {
@@ -17,6 +49,70 @@ impl fmt::Display for ReactionType {
             ReactionType::Project(_) => write!(f, "project"),
             ReactionType::None => write!(f, "NONE"),
             ReactionType::Notice => write!(f, "notice"),
+            ReactionType::Review => write!(f, "review"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ReactionPermission, ReactionType};
+    use crate::{Project, Section};
+
+    #[test]
+    fn reaction_permission() {
+        let section = ReactionType::Section(Some(Section::default()));
+        let project = ReactionType::Project(Some(Project::default()));
+
+        // Editors, roles and maintainers of the project
+        for reaction_type in [&section, &project, &ReactionType::Review] {
+            assert_eq!(
+                reaction_type.permission(true, false, true),
+                ReactionPermission::Allowed
+            );
+        }
+        assert_eq!(
+            project.permission(true, true, true),
+            ReactionPermission::Allowed
+        );
+
+        // Everyone can submit news
+        assert_eq!(
+            ReactionType::Notice.permission(false, false, true),
+            ReactionPermission::Allowed
+        );
+
+        // Maintainers are limited to their projects, like others if reactions are restricted
+        assert_eq!(
+            section.permission(false, true, true),
+            ReactionPermission::MaintainerOnly
+        );
+        assert_eq!(
+            project.permission(false, true, true),
+            ReactionPermission::MaintainerOnly
+        );
+        assert_eq!(
+            section.permission(false, false, true),
+            ReactionPermission::Denied
+        );
+
+        // Unrestricted reactions apply to maintainers too
+        assert_eq!(
+            section.permission(false, true, false),
+            ReactionPermission::Allowed
+        );
+        assert_eq!(
+            project.permission(false, false, false),
+            ReactionPermission::Allowed
+        );
+
+        assert_eq!(
+            ReactionType::Review.permission(false, true, false),
+            ReactionPermission::Denied
+        );
+        assert_eq!(
+            ReactionType::Review.permission(false, false, false),
+            ReactionPermission::Denied
+        );
+    }
+}
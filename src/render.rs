@@ -1,32 +1,141 @@
 use chrono::{DateTime, Utc};
-use matrix_sdk::room::RoomMember;
-use matrix_sdk::ruma::{EventId, OwnedMxcUri, OwnedUserId};
+use matrix_sdk::ruma::{EventId, OwnedEventId, OwnedMxcUri, OwnedUserId};
 use serde::{Deserialize, Serialize};
 
 use std::collections::{BTreeMap, HashSet};
-use std::sync::LazyLock;
 
-use crate::{Config, News, Project, Section};
+use crate::{Config, ImageProcessing, MediaFile, Mention, News, Project, ReviewState, Section};
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 struct RenderNews {
+    pub event_id: OwnedEventId,
     pub reporter_id: OwnedUserId,
     pub reporter_display_name: String,
     pub timestamp: DateTime<Utc>,
+    /// The text set by an editor with `!edit`, otherwise the reporter's message
     pub message: String,
+    pub message_html: Option<String>,
+    pub message_markdown: String,
+    /// The reporter's message, ignoring `!edit`
+    pub reporter_message: String,
+    pub reporter_message_html: Option<String>,
+    pub reporter_message_markdown: String,
+    /// The editor who set the text with `!edit`
+    pub edited_by: Option<OwnedUserId>,
+    pub mentions: Vec<Mention>,
     pub images: Vec<(String, OwnedMxcUri)>,
     pub videos: Vec<(String, OwnedMxcUri)>,
+    pub image_files: Vec<RenderMediaFile>,
+    pub video_files: Vec<RenderMediaFile>,
+    pub pinned: bool,
+    /// Position within the section / project set with `!order`
+    pub position: Option<u32>,
 }
 
-impl From<News> for RenderNews {
-    fn from(news: News) -> Self {
+impl RenderNews {
+    /// Image filenames are the ones of the processed images
+    fn new(news: &News, image_processing: &ImageProcessing) -> Self {
+        let image_files: Vec<RenderMediaFile> = news
+            .image_files()
+            .into_iter()
+            .map(|file| RenderMediaFile::image(file, image_processing))
+            .collect();
+        let images = image_files
+            .iter()
+            .map(|file| (file.filename.clone(), file.mxc_uri.clone()))
+            .collect();
+        let video_files = news
+            .video_files()
+            .into_iter()
+            .map(RenderMediaFile::from)
+            .collect();
+
         RenderNews {
+            event_id: news.event_id.clone(),
             reporter_id: news.reporter_id.clone(),
             reporter_display_name: news.reporter_display_name.clone(),
             timestamp: news.timestamp,
-            message: news.message(),
-            images: news.images(),
+            message: news.edited_message(),
+            message_html: news.edited_message_html(),
+            message_markdown: news.edited_message_markdown(),
+            reporter_message: news.message(),
+            reporter_message_html: news.message_html(),
+            reporter_message_markdown: news.message_markdown(),
+            edited_by: news.editor_edit().map(|edit| edit.editor_id),
+            mentions: news.mentions(),
+            images,
             videos: news.videos(),
+            image_files,
+            video_files,
+            pinned: news.is_pinned(),
+            position: news.position(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct RenderMediaFile {
+    /// Unique filename of the bundled file
+    pub filename: String,
+    pub original_filename: String,
+    pub mxc_uri: OwnedMxcUri,
+    pub caption: Option<String>,
+    /// The caption, or the filename if there's none
+    pub alt: String,
+    pub mimetype: Option<String>,
+    pub size: Option<u64>,
+    pub width: Option<u64>,
+    pub height: Option<u64>,
+    pub duration: Option<u64>,
+    pub blurhash: Option<String>,
+    pub thumbnail_uri: Option<OwnedMxcUri>,
+}
+
+impl RenderMediaFile {
+    /// Filename, MIME type and dimensions are the ones of the processed image
+    fn image(file: MediaFile, image_processing: &ImageProcessing) -> Self {
+        if !image_processing.processes(&file) {
+            return Self::from(file);
+        }
+
+        let filename = image_processing.filename(&file);
+        let mut render_file = Self::from(file);
+        render_file.filename = filename;
+        render_file.size = None;
+        if let Some(mimetype) = image_processing.format.mime_type() {
+            render_file.mimetype = Some(mimetype.to_owned());
+        }
+
+        if let (Some(max_width), Some(width), Some(height)) = (
+            image_processing.max_width.map(u64::from),
+            render_file.width,
+            render_file.height,
+        ) {
+            if width > max_width {
+                render_file.width = Some(max_width);
+                render_file.height = Some((height * max_width).div_ceil(width));
+            }
+        }
+
+        render_file
+    }
+}
+
+impl From<MediaFile> for RenderMediaFile {
+    fn from(file: MediaFile) -> Self {
+        RenderMediaFile {
+            filename: file.unique_filename(),
+            alt: file.caption.clone().unwrap_or(file.filename.clone()),
+            original_filename: file.filename,
+            mxc_uri: file.mxc_uri,
+            caption: file.caption,
+            mimetype: file.mimetype,
+            size: file.size,
+            width: file.width,
+            height: file.height,
+            duration: file.duration,
+            blurhash: file.blurhash,
+            thumbnail_uri: file.thumbnail_uri,
         }
     }
 }
@@ -53,8 +162,9 @@ pub struct RenderResult {
     pub rendered: String,
     pub warnings: Vec<String>,
     pub notes: Vec<String>,
-    pub images: Vec<(String, OwnedMxcUri)>,
-    pub videos: Vec<(String, OwnedMxcUri)>,
+    /// Images with their original filenames, see `ImageProcessing::filename()`
+    pub images: Vec<MediaFile>,
+    pub videos: Vec<MediaFile>,
 }
 
 fn template_filter_timedelta(
@@ -90,33 +200,21 @@ fn template_filter_timedelta(
     ))
 }
 
-static TEMPLATE_TEXT: LazyLock<String> = LazyLock::new(|| {
-    use std::io::Read;
-
-    let path = std::env::var("TEMPLATE_PATH").unwrap_or("template.md".into());
-    debug!("Reading template from file path: {:?}", path);
-
-    let mut text = String::new();
-    std::fs::File::open(path)
-        .expect("Unable to open template file")
-        .read_to_string(&mut text)
-        .expect("Unable to read template file");
-
-    text
-});
-
-static JINJA_ENV: LazyLock<minijinja::Environment> = LazyLock::new(|| {
+/// Creates a template environment with all filters and functions registered
+pub fn environment() -> minijinja::Environment<'static> {
     let mut env = minijinja::Environment::new();
     minijinja_contrib::add_to_environment(&mut env);
-    env.add_template("template", &TEMPLATE_TEXT).unwrap();
     env.add_filter("timedelta", template_filter_timedelta);
     env
-});
+}
 
 pub fn render(
-    news_list: Vec<News>,
+    mut news_list: Vec<News>,
     config: Config,
-    editor: &RoomMember,
+    env: &minijinja::Environment,
+    template_name: &str,
+    editor: &str,
+    timestamp: DateTime<Utc>,
 ) -> Result<RenderResult, minijinja::Error> {
     let mut render_projects: BTreeMap<String, RenderProject> = BTreeMap::new();
     let mut render_sections: BTreeMap<String, RenderSection> = BTreeMap::new();
@@ -125,12 +223,15 @@ pub fn render(
     let mut not_assigned = 0;
     let mut project_names: HashSet<String> = HashSet::new();
 
-    let mut images: Vec<(String, OwnedMxcUri)> = Vec::new();
-    let mut videos: Vec<(String, OwnedMxcUri)> = Vec::new();
+    let mut images: Vec<MediaFile> = Vec::new();
+    let mut videos: Vec<MediaFile> = Vec::new();
 
     let mut warnings: Vec<String> = Vec::new();
     let mut notes: Vec<String> = Vec::new();
 
+    // Process the news in submission order, so warnings and media files are stable
+    news_list.sort_by(|a, b| (a.timestamp, &a.event_id).cmp(&(b.timestamp, &b.event_id)));
+
     // Sort news entries into `RenderProject`s (`render_projects`)
     for news in news_list {
         let message_link = message_link(&config, &news.event_id);
@@ -152,12 +253,38 @@ pub fn render(
             continue;
         }
 
+        // Check if the news entry got approved by enough editors
+        if let ReviewState::Assigned {
+            approvals,
+            required,
+        } = news.review_state(config.review.required_approvals)
+        {
+            if config.review.skip_unreviewed {
+                warnings.insert(0, format!("[{}] News entry by {} isn’t reviewed yet ({}/{} approvals), it’ll not appear in the rendered output.", message_link, news.reporter_display_name, approvals, required));
+                continue;
+            }
+            warnings.insert(
+                0,
+                format!(
+                    "[{}] News entry by {} isn’t reviewed yet ({}/{} approvals).",
+                    message_link, news.reporter_display_name, approvals, required
+                ),
+            );
+        }
+
+        // Check if the reporter changed the message after an editor edited it
+        if news.editor_edit().is_some_and(|edit| edit.conflict) {
+            warnings.insert(0, format!("[{}] News entry by {} got changed by the reporter after it was edited with !edit, the changes are not included in the edited text.", message_link, news.reporter_display_name));
+        }
+
         // The news entry is assigned to a project / section, and will be rendered -> increase counter.
         news_count += 1;
 
         // Get news images / videos
-        images.append(&mut news.images());
-        videos.append(&mut news.videos());
+        images.append(&mut news.image_files());
+        videos.append(&mut news.video_files());
+
+        let render_news = RenderNews::new(&news, &config.media.images);
 
         // Add news entries without any project information (but with section information) directly to the specified `RenderSection`
         if news.project_names().is_empty() {
@@ -170,14 +297,14 @@ pub fn render(
                 match render_sections.get_mut(&map_section_name) {
                     // RenderSection already exists -> Add news entry to it
                     Some(render_section) => {
-                        render_section.news.insert(0, news.clone().into());
+                        render_section.news.insert(0, render_news.clone());
                     }
                     // RenderSection doesn't exist yet -> Create it, and add news entry to it
                     None => {
                         let render_section = RenderSection {
                             section,
                             projects: Vec::new(),
-                            news: vec![news.clone().into()],
+                            news: vec![render_news.clone()],
                         };
                         render_sections.insert(map_section_name, render_section);
                     }
@@ -202,12 +329,12 @@ pub fn render(
 
                     match render_projects.get_mut(&custom_project_section_name) {
                         // RenderProject already exists -> Add news entry to it
-                        Some(render_project) => render_project.news.insert(0, news.clone().into()),
+                        Some(render_project) => render_project.news.insert(0, render_news.clone()),
                         // RenderProject doesn't exist yet -> Create it, and add news entry to it
                         None => {
                             let render_project = RenderProject {
                                 project: project.clone(),
-                                news: vec![news.clone().into()],
+                                news: vec![render_news.clone()],
                                 overwritten_section: Some(section_name),
                             };
                             render_projects
@@ -224,12 +351,12 @@ pub fn render(
             // Standard (news entry doesn't use a custom section)
             match render_projects.get_mut(&news_project_name) {
                 // RenderProject already exists -> Add news entry to it
-                Some(render_project) => render_project.news.insert(0, news.clone().into()),
+                Some(render_project) => render_project.news.insert(0, render_news.clone()),
                 // RenderProject doesn't exist yet -> Create it, and add news entry to it
                 None => {
                     let render_project = RenderProject {
                         project,
-                        news: vec![news.clone().into()],
+                        news: vec![render_news.clone()],
                         overwritten_section: None,
                     };
                     render_projects.insert(news_project_name, render_project);
@@ -266,6 +393,14 @@ pub fn render(
         }
     }
 
+    // Order the news entries within each section and project
+    for render_section in render_sections.values_mut() {
+        sort_news(&mut render_section.news);
+        for render_project in &mut render_section.projects {
+            sort_news(&mut render_project.news);
+        }
+    }
+
     // Sort sections
     let mut sorted_render_sections: BTreeMap<Section, RenderSection> = BTreeMap::new();
     for render_section in render_sections.values() {
@@ -293,14 +428,14 @@ pub fn render(
     warnings.reverse();
     notes.reverse();
 
-    let rendered = JINJA_ENV
-        .get_template("template")?
+    let rendered = env
+        .get_template(template_name)?
         .render(minijinja::context! {
-            timestamp => time::OffsetDateTime::now_utc(),
+            timestamp => time::OffsetDateTime::from(std::time::SystemTime::from(timestamp)),
             sections => render_sections,
             projects => project_names,
             config => config,
-            editor => editor.name(),
+            editor => editor,
         })?;
 
     Ok(RenderResult {
@@ -312,6 +447,24 @@ pub fn render(
     })
 }
 
+/// Orders news entries: pinned ones first, then by submission time.
+/// Afterwards, entries with a position set by an editor are moved to that position.
+fn sort_news(news: &mut Vec<RenderNews>) {
+    news.sort_by(|a, b| {
+        (!a.pinned, a.timestamp, &a.event_id).cmp(&(!b.pinned, b.timestamp, &b.event_id))
+    });
+
+    let (mut positioned, rest): (Vec<_>, Vec<_>) =
+        news.drain(..).partition(|n| n.position.is_some());
+    *news = rest;
+
+    positioned.sort_by_key(|n| n.position);
+    for n in positioned {
+        let index = n.position.unwrap_or_default().saturating_sub(1) as usize;
+        news.insert(index.min(news.len()), n);
+    }
+}
+
 fn message_link(config: &Config, event_id: &EventId) -> String {
     let room_id = config.reporting_room_id.clone();
     format!(
@@ -319,3 +472,48 @@ fn message_link(config: &Config, event_id: &EventId) -> String {
         room_id, event_id
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use super::{sort_news, RenderNews};
+    use crate::{ImageProcessing, News};
+
+    #[test]
+    fn news_order() {
+        let now = Utc::now();
+        let news = |event_id: &str, minutes: i64| {
+            let mut news = News::test_entry(event_id);
+            news.timestamp = now + Duration::minutes(minutes);
+            news
+        };
+        let render = |news: &News| RenderNews::new(news, &ImageProcessing::default());
+        let event_ids = |news: &[RenderNews]| -> Vec<String> {
+            news.iter().map(|n| n.event_id.to_string()).collect()
+        };
+
+        let (first, second, third, fourth) = (
+            news("$first", 0),
+            news("$second", 1),
+            news("$third", 2),
+            news("$fourth", 3),
+        );
+        let mut list: Vec<RenderNews> = [&fourth, &second, &first, &third]
+            .into_iter()
+            .map(render)
+            .collect();
+        sort_news(&mut list);
+        assert_eq!(event_ids(&list), ["$first", "$second", "$third", "$fourth"]);
+
+        third.set_pinned(true);
+        first.set_position(Some(3));
+        fourth.set_position(Some(10));
+        let mut list: Vec<RenderNews> = [&fourth, &second, &first, &third]
+            .into_iter()
+            .map(render)
+            .collect();
+        sort_news(&mut list);
+        assert_eq!(event_ids(&list), ["$third", "$second", "$first", "$fourth"]);
+    }
+}
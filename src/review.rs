@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+
+/// Review workflow settings (`[review]` config section)
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReviewConfig {
+    /// Number of distinct editors who have to approve a news entry, `0` disables reviews
+    #[serde(default)]
+    pub required_approvals: usize,
+    /// Editors approve news entries by reacting with this emoji (or with `!approve`)
+    pub emoji: Option<String>,
+    /// Leave news entries which aren't reviewed yet out of the rendered output,
+    /// instead of only warning about them
+    #[serde(default)]
+    pub skip_unreviewed: bool,
+}
+
+impl ReviewConfig {
+    pub fn enabled(&self) -> bool {
+        self.required_approvals > 0
+    }
+}
+
+/// Lifecycle of a news entry: submitted → assigned → reviewed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReviewState {
+    /// Not assigned to a project / section yet
+    Submitted,
+    /// Assigned, but doesn't have enough approvals yet
+    Assigned {
+        approvals: usize,
+        required: usize,
+    },
+    Reviewed,
+}
+
+impl fmt::Display for ReviewState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReviewState::Submitted => write!(f, "submitted"),
+            ReviewState::Assigned {
+                approvals,
+                required,
+            } => write!(f, "assigned, {}/{} approvals", approvals, required),
+            ReviewState::Reviewed => write!(f, "reviewed"),
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use matrix_sdk::ruma::OwnedUserId;
+use serde::{Deserialize, Serialize};
+
+use crate::ReactionType;
+
+/// Admin room commands, which can be allowed for roles
+pub const ADMIN_COMMANDS: &[&str] = &[
+    "!about",
+    "!approve",
+    "!assign",
+    "!attach",
+    "!clear",
+    "!details",
+    "!edit",
+    "!editions",
+    "!help",
+    "!list-config",
+    "!list-projects",
+    "!list-sections",
+    "!move",
+    "!order",
+    "!pin",
+    "!publish",
+    "!render",
+    "!rerender",
+    "!reply",
+    "!restart",
+    "!resync",
+    "!say",
+    "!show-edition",
+    "!status",
+    "!unapprove",
+    "!unassign",
+    "!update-config",
+];
+
+/// A group of users with limited permissions (`[[roles]]` config section).
+/// Users listed in `editors` have all permissions.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Role {
+    pub name: String,
+    pub users: Vec<OwnedUserId>,
+    /// Admin room commands the role can use (e.g. `!status`), `*` for all
+    #[serde(default)]
+    pub commands: Vec<String>,
+    /// Reactions the role can use: `*` for all, a kind (`notice`, `review`, `section`, `project`),
+    /// or the name of a section / project
+    #[serde(default)]
+    pub reactions: Vec<String>,
+}
+
+impl Role {
+    pub fn allows_command(&self, command: &str) -> bool {
+        self.commands.iter().any(|c| c == "*" || c == command)
+    }
+
+    pub fn allows_reaction(&self, reaction_type: &ReactionType) -> bool {
+        let (kind, name) = match reaction_type {
+            ReactionType::Section(section) => ("section", section.as_ref().map(|s| &s.name)),
+            ReactionType::Project(project) => ("project", project.as_ref().map(|p| &p.name)),
+            ReactionType::Notice => ("notice", None),
+            ReactionType::Review => ("review", None),
+            ReactionType::None => return false,
+        };
+
+        self.reactions
+            .iter()
+            .any(|r| r == "*" || r == kind || Some(r) == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use matrix_sdk::ruma::owned_user_id;
+
+    use super::Role;
+    use crate::{Project, ReactionType, Section};
+
+    #[test]
+    fn role_permissions() {
+        let role = Role {
+            name: "gnome-circle".to_owned(),
+            users: vec![owned_user_id!("@lead:matrix.local")],
+            commands: vec!["!status".to_owned(), "!assign".to_owned()],
+            reactions: vec!["review".to_owned(), "shortwave".to_owned()],
+        };
+        let project = |name: &str| Project {
+            name: name.to_owned(),
+            ..Default::default()
+        };
+
+        assert!(role.allows_command("!status"));
+        assert!(!role.allows_command("!clear"));
+        assert!(role.allows_reaction(&ReactionType::Review));
+        assert!(role.allows_reaction(&ReactionType::Project(Some(project("shortwave")))));
+        assert!(!role.allows_reaction(&ReactionType::Project(Some(project("fragments")))));
+        assert!(!role.allows_reaction(&ReactionType::Section(Some(Section::default()))));
+        assert!(!role.allows_reaction(&ReactionType::Notice));
+    }
+}
@@ -0,0 +1,106 @@
+use matrix_sdk::authentication::matrix::MatrixSession;
+use serde::{Deserialize, Serialize};
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+use crate::Error;
+
+/// The Matrix session, persisted in the state directory so the bot
+/// doesn't need to log in (and create a new device) on every start
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Session {
+    pub homeserver_url: String,
+    pub matrix_session: MatrixSession,
+    /// The `next_batch` token of the last sync
+    #[serde(default)]
+    pub sync_token: Option<String>,
+}
+
+impl Session {
+    pub fn new(homeserver_url: String, matrix_session: MatrixSession) -> Self {
+        Self {
+            homeserver_url,
+            matrix_session,
+            sync_token: None,
+        }
+    }
+
+    /// Loads the stored session, if there's a valid one
+    pub fn load(state_dir: &Path) -> Option<Self> {
+        let data = fs::read_to_string(Self::path(state_dir)).ok()?;
+        serde_json::from_str(&data)
+            .inspect_err(|err| warn!("Unable to parse stored session: {}", err))
+            .ok()
+    }
+
+    /// Writes the session, it's only readable by the bot user as it contains the access token
+    pub fn save(&self, state_dir: &Path) -> Result<(), Error> {
+        let data = serde_json::to_string_pretty(self)?;
+        let path = Self::path(state_dir);
+        let tmp_path = path.with_extension("json.tmp");
+
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&tmp_path)?;
+        file.write_all(data.as_bytes())?;
+        fs::rename(tmp_path, path)?;
+
+        Ok(())
+    }
+
+    fn path(state_dir: &Path) -> PathBuf {
+        state_dir.join("session.json")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use matrix_sdk::authentication::matrix::MatrixSession;
+    use matrix_sdk::ruma::{owned_device_id, owned_user_id};
+    use matrix_sdk::{SessionMeta, SessionTokens};
+
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::Session;
+
+    #[test]
+    fn session_roundtrip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let state_dir = temp_dir.path();
+        assert!(Session::load(state_dir).is_none());
+
+        let mut session = Session::new(
+            "https://matrix.local/".to_owned(),
+            MatrixSession {
+                meta: SessionMeta {
+                    user_id: owned_user_id!("@hebbot:matrix.local"),
+                    device_id: owned_device_id!("HEBBOT"),
+                },
+                tokens: SessionTokens {
+                    access_token: "secret".to_owned(),
+                    refresh_token: None,
+                },
+            },
+        );
+        session.sync_token = Some("s1234".to_owned());
+        session.save(state_dir).unwrap();
+
+        let stored = Session::load(state_dir).unwrap();
+        assert_eq!(stored.matrix_session.meta, session.matrix_session.meta);
+        assert_eq!(stored.matrix_session.tokens.access_token, "secret");
+        assert_eq!(stored.sync_token.as_deref(), Some("s1234"));
+
+        let mode = fs::metadata(state_dir.join("session.json"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
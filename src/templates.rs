@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::{render, Error};
+
+type ModifiedTimes = Arc<Mutex<HashMap<PathBuf, Option<SystemTime>>>>;
+
+/// Loads the templates from disk, and reloads them when a file changed.
+///
+/// Template names are resolved relative to the template directory (or the working
+/// directory if none is set), so templates can `{% include %}` / `{% import %}` each other.
+pub struct Templates {
+    dir: Option<PathBuf>,
+    names: Vec<String>,
+    env: Option<minijinja::Environment<'static>>,
+    // Modification time of every loaded template file
+    modified: ModifiedTimes,
+}
+
+impl Templates {
+    pub fn new(dir: Option<PathBuf>, names: Vec<String>) -> Self {
+        Self {
+            dir,
+            names,
+            env: None,
+            modified: ModifiedTimes::default(),
+        }
+    }
+
+    /// Returns the template environment, the templates get reloaded
+    /// first if they haven't been loaded yet or changed on disk
+    pub fn environment(&mut self) -> Result<&minijinja::Environment<'static>, Error> {
+        if self.env.is_none() || self.has_changed() {
+            self.load()?;
+        }
+
+        Ok(self.env.as_ref().unwrap())
+    }
+
+    /// (Re)loads the templates from disk, and validates the configured templates
+    /// and every template inside the template directory.
+    /// Syntax errors are returned as `Error::Template`, files of the template directory
+    /// which aren't text (e.g. images) are skipped.
+    pub fn load(&mut self) -> Result<(), Error> {
+        let dir = self.dir.clone().unwrap_or(PathBuf::from("."));
+        let modified = ModifiedTimes::default();
+
+        let mut env = render::environment();
+        let loader_modified = modified.clone();
+        env.set_loader(move |name| load_template(&dir, name, &loader_modified));
+
+        for name in &self.names {
+            env.get_template(name)?;
+        }
+
+        // Partials and macros are only loaded when rendering, so validate them now too
+        if let Some(dir) = &self.dir {
+            for name in template_names(dir, dir)? {
+                if self.names.contains(&name) {
+                    continue;
+                }
+
+                if !is_text_file(&dir.join(&name)) {
+                    warn!(
+                        "Skipping {:?} of the template directory, it isn't a text file",
+                        name
+                    );
+                    continue;
+                }
+                env.get_template(&name)?;
+            }
+        }
+
+        self.env = Some(env);
+        self.modified = modified;
+        Ok(())
+    }
+
+    fn has_changed(&self) -> bool {
+        let changed = self
+            .modified
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(path, time)| modified(path) != *time);
+
+        if changed {
+            info!("Template changed on disk, reloading…");
+        }
+        changed
+    }
+}
+
+fn load_template(
+    dir: &Path,
+    name: &str,
+    modified_times: &ModifiedTimes,
+) -> Result<Option<String>, minijinja::Error> {
+    let path = dir.join(name);
+    debug!("Reading template from file path: {:?}", path);
+
+    // Also track missing templates, to notice when they get created
+    modified_times
+        .lock()
+        .unwrap()
+        .insert(path.clone(), modified(&path));
+
+    match fs::read_to_string(&path) {
+        Ok(text) => Ok(Some(text)),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(minijinja::Error::new(
+            minijinja::ErrorKind::InvalidOperation,
+            format!("could not read template {:?}", path),
+        )
+        .with_source(err)),
+    }
+}
+
+/// Names of all templates inside the template directory (hidden files are skipped)
+fn template_names(dir: &Path, base: &Path) -> Result<Vec<String>, Error> {
+    let mut names = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path
+            .file_name()
+            .is_some_and(|n| n.to_string_lossy().starts_with('.'))
+        {
+            continue;
+        }
+
+        if path.is_dir() {
+            names.append(&mut template_names(&path, base)?);
+        } else if let Ok(name) = path.strip_prefix(base) {
+            let components: Vec<_> = name
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect();
+            names.push(components.join("/"));
+        }
+    }
+
+    Ok(names)
+}
+
+/// The directory can contain other files too, e.g. images
+fn is_text_file(path: &Path) -> bool {
+    fs::read(path).is_ok_and(|bytes| std::str::from_utf8(&bytes).is_ok())
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches2::assert_matches;
+
+    use std::fs;
+    use std::path::Path;
+    use std::time::{Duration, SystemTime};
+
+    use super::Templates;
+    use crate::Error;
+
+    fn render(templates: &mut Templates, name: &str) -> String {
+        let env = templates.environment().unwrap();
+        env.get_template(name).unwrap().render(()).unwrap()
+    }
+
+    /// Writes the file with a modification time in the future,
+    /// as the file system might not notice changes within the same second
+    fn write_changed(path: &Path, content: &str) {
+        fs::write(path, content).unwrap();
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(10))
+            .unwrap();
+    }
+
+    #[test]
+    fn reload_changed_template() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path().to_owned();
+        fs::write(dir.join("template.md"), "First version").unwrap();
+
+        let mut templates = Templates::new(Some(dir.clone()), vec!["template.md".to_owned()]);
+        assert_eq!(render(&mut templates, "template.md"), "First version");
+
+        write_changed(&dir.join("template.md"), "Second version");
+        assert_eq!(render(&mut templates, "template.md"), "Second version");
+    }
+
+    #[test]
+    fn include_and_import_templates() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path().to_owned();
+        fs::create_dir_all(dir.join("partials")).unwrap();
+        fs::write(
+            dir.join("template.md"),
+            "{% import \"macros.md\" as macros %}{% include \"partials/header.md\" %} {{ macros.bold(\"news\") }}",
+        )
+        .unwrap();
+        fs::write(dir.join("partials/header.md"), "# This Week").unwrap();
+        fs::write(
+            dir.join("macros.md"),
+            "{% macro bold(text) %}**{{ text }}**{% endmacro %}",
+        )
+        .unwrap();
+
+        // Files which aren't templates are skipped
+        fs::write(dir.join("logo.png"), [0x89, 0x50, 0x4e, 0x47, 0xff, 0xfe]).unwrap();
+
+        let mut templates = Templates::new(Some(dir.clone()), vec!["template.md".to_owned()]);
+        templates.load().unwrap();
+        assert_eq!(
+            render(&mut templates, "template.md"),
+            "# This Week **news**"
+        );
+
+        // Changed includes get reloaded too
+        write_changed(&dir.join("partials/header.md"), "# This Month");
+        assert_eq!(
+            render(&mut templates, "template.md"),
+            "# This Month **news**"
+        );
+    }
+
+    #[test]
+    fn template_syntax_error() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path().to_owned();
+        fs::write(dir.join("template.md"), "{% if %}Broken{% endif %}").unwrap();
+
+        let mut templates = Templates::new(Some(dir.clone()), vec!["template.md".to_owned()]);
+        assert_matches!(templates.load(), Err(Error::Template(err)));
+        assert_eq!(err.name(), Some("template.md"));
+        assert!(Error::Template(err).to_string().contains("template.md"));
+    }
+
+    #[test]
+    fn partial_syntax_error() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path().to_owned();
+        fs::write(dir.join("template.md"), "{{ 1 + 1 }}").unwrap();
+        fs::write(dir.join("macros.md"), "{% macro broken( %}{% endmacro %}").unwrap();
+
+        // Templates which aren't configured nor included yet are validated too
+        let mut templates = Templates::new(Some(dir.clone()), vec!["template.md".to_owned()]);
+        assert_matches!(templates.load(), Err(Error::Template(err)));
+        assert_eq!(err.name(), Some("macros.md"));
+    }
+}
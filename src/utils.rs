@@ -1,14 +1,18 @@
 use async_process::{Command, Stdio};
 use matrix_sdk::deserialized_responses::TimelineEventKind;
-use matrix_sdk::room::Room;
+use matrix_sdk::room::{IncludeRelations, RelationsOptions, Room};
+use matrix_sdk::ruma::api::client::error::ErrorKind;
+use matrix_sdk::ruma::events::relation::RelationType;
+use matrix_sdk::ruma::events::room::member::MembershipState;
 use matrix_sdk::ruma::events::room::message::{
-    ImageMessageEventContent, MessageType, NoticeMessageEventContent, OriginalSyncRoomMessageEvent,
-    Relation, TextMessageEventContent, VideoMessageEventContent,
+    ImageMessageEventContent, MessageFormat, MessageType, NoticeMessageEventContent,
+    OriginalSyncRoomMessageEvent, Relation, TextMessageEventContent, VideoMessageEventContent,
 };
+use matrix_sdk::ruma::events::Mentions;
 use matrix_sdk::ruma::events::{
     AnySyncMessageLikeEvent, AnySyncTimelineEvent, SyncMessageLikeEvent,
 };
-use matrix_sdk::ruma::{EventId, UserId};
+use matrix_sdk::ruma::{EventId, OwnedUserId, UserId};
 use regex::Regex;
 
 use std::fmt::Write;
@@ -16,6 +20,8 @@ use std::fs::File;
 use std::io::Read;
 use std::{env, str};
 
+use crate::Error;
+
 /// Helper trait for room message events.
 ///
 /// The main feature of this trait is that it always fetches the message
@@ -28,17 +34,29 @@ pub trait MessageEventExt {
     /// The message type.
     fn msgtype(&self) -> &MessageType;
 
+    /// The mentions of the message, if any.
+    fn mentions(&self) -> Option<&Mentions>;
+
     ///If this message is an edit, the related event ID.
     fn edited_event_id(&self) -> Option<&EventId>;
 
     /// The text of the message, if any.
     fn text(&self, allow_notice: bool) -> Option<&str>;
 
+    /// The HTML formatted text of the message, if any.
+    fn html(&self, allow_notice: bool) -> Option<&str>;
+
     /// The image of the message, if any.
     fn image(&self) -> Option<&ImageMessageEventContent>;
 
     /// The video of the message, if any.
     fn video(&self) -> Option<&VideoMessageEventContent>;
+
+    /// The thread root and the replied message, if any.
+    fn thread_and_reply_ids(&self) -> Vec<&EventId>;
+
+    /// If this message is posted in a thread, the thread root ID.
+    fn thread_root_id(&self) -> Option<&EventId>;
 }
 
 impl MessageEventExt for OriginalSyncRoomMessageEvent {
@@ -58,6 +76,22 @@ impl MessageEventExt for OriginalSyncRoomMessageEvent {
         }
     }
 
+    fn mentions(&self) -> Option<&Mentions> {
+        if let Some(Relation::Replacement(edit)) = self
+            .unsigned
+            .relations
+            .replace
+            .as_deref()
+            .and_then(|edit| edit.content.relates_to.as_ref())
+        {
+            edit.new_content.mentions.as_ref()
+        } else if let Some(Relation::Replacement(edit)) = &self.content.relates_to {
+            edit.new_content.mentions.as_ref()
+        } else {
+            self.content.mentions.as_ref()
+        }
+    }
+
     fn edited_event_id(&self) -> Option<&EventId> {
         if let Some(Relation::Replacement(edit)) = &self.content.relates_to {
             Some(&edit.event_id)
@@ -76,6 +110,18 @@ impl MessageEventExt for OriginalSyncRoomMessageEvent {
         }
     }
 
+    fn html(&self, allow_notice: bool) -> Option<&str> {
+        let formatted = match self.msgtype() {
+            MessageType::Text(TextMessageEventContent { formatted, .. }) => formatted.as_ref(),
+            MessageType::Notice(NoticeMessageEventContent { formatted, .. }) if allow_notice => {
+                formatted.as_ref()
+            }
+            _ => None,
+        }?;
+
+        (formatted.format == MessageFormat::Html).then_some(formatted.body.as_str())
+    }
+
     fn image(&self) -> Option<&ImageMessageEventContent> {
         if let MessageType::Image(content) = self.msgtype() {
             Some(content)
@@ -91,23 +137,102 @@ impl MessageEventExt for OriginalSyncRoomMessageEvent {
             None
         }
     }
+
+    fn thread_and_reply_ids(&self) -> Vec<&EventId> {
+        match &self.content.relates_to {
+            Some(Relation::Reply { in_reply_to }) => vec![&in_reply_to.event_id],
+            Some(Relation::Thread(thread)) => {
+                let mut ids: Vec<&EventId> = vec![&thread.event_id];
+                if let Some(in_reply_to) = &thread.in_reply_to {
+                    ids.push(&in_reply_to.event_id);
+                }
+                ids
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn thread_root_id(&self) -> Option<&EventId> {
+        if let Some(Relation::Thread(thread)) = &self.content.relates_to {
+            Some(&thread.event_id)
+        } else {
+            None
+        }
+    }
 }
 
 /// Get room message by event id
 pub async fn room_event_by_id(room: &Room, event_id: &EventId) -> Option<AnySyncTimelineEvent> {
-    let timeline_event = room.event(event_id, None).await.ok()?;
-
-    match timeline_event.kind {
-        TimelineEventKind::PlainText { event } => event.deserialize().ok(),
-        ev => {
-            // This covers the other variants: DecryptedRoomEvent and UnableToDecrypt.
-            // At the moment Hebbot does not support being used in encrypted rooms.
-            warn!("Unsupported E2EE event: {ev:?}");
+    match fetch_room_event(room, event_id).await {
+        Ok(event) => event,
+        Err(err) => {
+            warn!("Unable to fetch event {}: {}", event_id, err);
             None
         }
     }
 }
 
+/// Get room message by event id, `None` if the room has no such event
+pub async fn fetch_room_event(
+    room: &Room,
+    event_id: &EventId,
+) -> Result<Option<AnySyncTimelineEvent>, Error> {
+    let timeline_event = match room.event(event_id, None).await {
+        Ok(timeline_event) => timeline_event,
+        Err(err) if err.client_api_error_kind() == Some(&ErrorKind::NotFound) => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+
+    if let TimelineEventKind::UnableToDecrypt { utd_info, .. } = &timeline_event.kind {
+        debug!(
+            "Unable to decrypt event {}: {:?}",
+            event_id, utd_info.reason
+        );
+        return Err(Error::UnableToDecrypt(event_id.to_owned()));
+    }
+
+    // Decrypted events are exposed as sync events too
+    Ok(Some(timeline_event.raw().deserialize()?))
+}
+
+/// Whether the user currently joined the room
+pub async fn is_joined_member(room: &Room, user_id: &UserId) -> bool {
+    matches!(
+        room.get_member(user_id).await,
+        Ok(Some(member)) if *member.membership() == MembershipState::Join
+    )
+}
+
+/// Get all events with the given relation type to an event, e.g. reactions
+pub async fn room_event_relations(
+    room: &Room,
+    event_id: &EventId,
+    relation_type: RelationType,
+) -> Result<Vec<AnySyncTimelineEvent>, Error> {
+    let mut events = Vec::new();
+    let mut from = None;
+    loop {
+        let options = RelationsOptions {
+            from,
+            include_relations: IncludeRelations::RelationsOfType(relation_type.clone()),
+            ..Default::default()
+        };
+
+        let relations = room.relations(event_id.to_owned(), options).await?;
+        events.extend(
+            relations
+                .chunk
+                .iter()
+                .filter_map(|event| event.raw().deserialize().ok()),
+        );
+        from = relations.next_batch_token;
+
+        if from.is_none() {
+            return Ok(events);
+        }
+    }
+}
+
 /// Get the given event as a message event, if it is one.
 pub fn as_message_event(
     room_event: &AnySyncTimelineEvent,
@@ -164,6 +289,39 @@ pub fn remove_bot_name(bot: &UserId, display_name: Option<String>, msg: &str) ->
     msg.trim().to_string()
 }
 
+/// Remove bot mention pill or name from the beginning of a HTML formatted message
+pub fn remove_bot_pill(bot: &UserId, display_name: Option<String>, html: &str) -> String {
+    let mut names = vec![format!(
+        "@?{}(:{})?",
+        regex::escape(bot.localpart()),
+        regex::escape(bot.server_name().as_str())
+    )];
+    if let Some(display_name) = display_name {
+        names.push(regex::escape(&display_name));
+    }
+
+    let regex = format!(
+        r#"(?i)^(\s*<p>)?\s*(<a href="https://matrix\.to/#/@?{}(:|%3A){}"[^>]*>[^<]*</a>|{}):?\s*"#,
+        regex::escape(bot.localpart()),
+        regex::escape(bot.server_name().as_str()),
+        names.join("|")
+    );
+    let re = Regex::new(&regex).unwrap();
+    re.replace(html, "$1").trim().to_string()
+}
+
+/// Matrix user ids written as plain text in a message
+pub fn user_ids_in_text(msg: &str) -> Vec<OwnedUserId> {
+    let re = Regex::new(r"@[a-zA-Z0-9._=\-/+]+:[a-zA-Z0-9.\-]+(:[0-9]+)?").unwrap();
+    re.find_iter(msg)
+        .filter_map(|m| {
+            // Trailing dots are most likely the end of a sentence
+            let user_id = m.as_str().trim_end_matches('.');
+            UserId::parse(user_id).ok()
+        })
+        .collect()
+}
+
 pub fn format_messages(is_warning: bool, list: &[String]) -> String {
     let emoji = if is_warning { "⚠️" } else { "ℹ️" };
 
@@ -225,7 +383,7 @@ mod tests {
     use matrix_sdk::ruma::{event_id, user_id, EventId, UserId};
     use serde_json::json;
 
-    use super::{msg_starts_with_mention, remove_bot_name, MessageEventExt};
+    use super::{msg_starts_with_mention, remove_bot_name, remove_bot_pill, MessageEventExt};
 
     static ORIGINAL_EVENT_ID: LazyLock<&'static EventId> = LazyLock::new(|| event_id!("$original"));
     static EDIT_EVENT_ID: LazyLock<&'static EventId> = LazyLock::new(|| event_id!("$edit"));
@@ -444,6 +602,39 @@ mod tests {
         assert_matches!(event.video(), None);
     }
 
+    #[test]
+    fn message_event_ext_thread_and_reply_ids() {
+        let image = json!({
+            "msgtype": "m.image",
+            "body": "image.png",
+            "url": "mxc://matrix.local/01234",
+        });
+        let event: OriginalSyncRoomMessageEvent =
+            serde_json::from_value(room_message_event(&ORIGINAL_EVENT_ID, image.clone())).unwrap();
+        assert!(event.thread_and_reply_ids().is_empty());
+
+        let mut reply = image.clone();
+        reply["m.relates_to"] = json!({ "m.in_reply_to": { "event_id": "$news" } });
+        let event: OriginalSyncRoomMessageEvent =
+            serde_json::from_value(room_message_event(&ORIGINAL_EVENT_ID, reply)).unwrap();
+        assert_eq!(event.thread_and_reply_ids(), vec![event_id!("$news")]);
+
+        let mut thread = image;
+        thread["m.relates_to"] = json!({
+            "rel_type": "m.thread",
+            "event_id": "$news",
+            "is_falling_back": true,
+            "m.in_reply_to": { "event_id": "$latest" },
+        });
+        let event: OriginalSyncRoomMessageEvent =
+            serde_json::from_value(room_message_event(&ORIGINAL_EVENT_ID, thread)).unwrap();
+        assert_eq!(
+            event.thread_and_reply_ids(),
+            vec![event_id!("$news"), event_id!("$latest")]
+        );
+        assert_eq!(event.thread_root_id(), Some(event_id!("$news")));
+    }
+
     #[test]
     fn message_event_ext_video() {
         // Original event.
@@ -680,4 +871,40 @@ mod tests {
             assert_eq!(remove_bot_name(uppercase_user_id, None, &message), message);
         }
     }
+
+    #[test]
+    fn message_event_ext_html_and_remove_bot_pill() {
+        let event: OriginalSyncRoomMessageEvent = serde_json::from_value(room_message_event(
+            &ORIGINAL_EVENT_ID,
+            json!({
+                "msgtype": "m.text",
+                "body": "hebbot: Hello **friend**!",
+                "format": "org.matrix.custom.html",
+                "formatted_body": "<a href=\"https://matrix.to/#/@hebbot:matrix.local\">Hebbot</a>: Hello <b>friend</b>!",
+            }),
+        ))
+        .unwrap();
+
+        let html = event.html(false).unwrap();
+        let bot_id = user_id!("@hebbot:matrix.local");
+        assert_eq!(
+            remove_bot_pill(bot_id, Some("Hebbot".to_owned()), html),
+            "Hello <b>friend</b>!"
+        );
+        assert_eq!(
+            remove_bot_pill(bot_id, None, "<p>hebbot: Hello</p><p>friend</p>"),
+            "<p>Hello</p><p>friend</p>"
+        );
+
+        // Plain text messages don't have HTML
+        let event: OriginalSyncRoomMessageEvent = serde_json::from_value(room_message_event(
+            &ORIGINAL_EVENT_ID,
+            json!({
+                "msgtype": "m.text",
+                "body": "hebbot: Hello friend!",
+            }),
+        ))
+        .unwrap();
+        assert_eq!(event.html(false), None);
+    }
 }